@@ -2,3 +2,20 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Cut the house keeps from every winning payout, in basis points.
+#[constant]
+pub const HOUSE_EDGE_BPS: u16 = 150;
+
+/// Lowest and highest `roll` a player can bet under. The bet wins when the
+/// resolved number, 1 to 100, is strictly below `roll`.
+#[constant]
+pub const MIN_ROLL: u8 = 2;
+
+#[constant]
+pub const MAX_ROLL: u8 = 96;
+
+/// Longest wait, in slots, a house can make players sit through before an
+/// unresolved bet can be refunded. Roughly a day.
+#[constant]
+pub const MAX_REFUND_TIMEOUT: u64 = 216_000;
@@ -1,7 +1,37 @@
 use anchor_lang::prelude::*;
 
 #[error_code]
-pub enum ErrorCode {
-    #[msg("Custom error message")]
-    CustomError,
+pub enum DiceError {
+    #[msg("Bet amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Roll must be between MIN_ROLL and MAX_ROLL")]
+    InvalidRoll,
+    #[msg("The vault cannot cover the payout of this bet")]
+    VaultTooSmall,
+    #[msg("The first instruction must be a single ed25519 signature check")]
+    Ed25519Program,
+    #[msg("The ed25519 instruction has accounts or cannot be verified")]
+    Ed25519Header,
+    #[msg("The bet was not signed by the house")]
+    Ed25519Pubkey,
+    #[msg("The signature does not match the bet")]
+    Ed25519Signature,
+    #[msg("Refund timeout must be between 1 and MAX_REFUND_TIMEOUT slots")]
+    InvalidRefundTimeout,
+    #[msg("The bet can only be refunded once the refund timeout has passed")]
+    BetNotTimedOut,
+    #[msg("The house has no open commitment to bet against")]
+    NoCommitment,
+    #[msg("Commit-reveal bets are resolved with reveal_bet")]
+    CommittedBet,
+    #[msg("The bet was not placed against a house commitment")]
+    NotCommittedBet,
+    #[msg("The commitment is still open for bets and cannot be revealed yet")]
+    CommitmentStillOpen,
+    #[msg("The revealed seed does not match the commitment")]
+    InvalidReveal,
+    #[msg("The house commitment is not the one the bet was placed against")]
+    CommitmentMismatch,
+    #[msg("Arithmetic overflow")]
+    Overflow,
 }
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::state::HouseConfig;
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    pub house: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"config", house.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> CommitSeed<'info> {
+    /// Opens commit-reveal bets against `sha256(seed)` of a new secret seed,
+    /// or closes them with `None`. Either way the previous commitment stops
+    /// taking bets, which is what allows its seed to be revealed.
+    pub fn commit_seed(&mut self, commitment: Option<[u8; 32]>) -> Result<()> {
+        self.config.commitment = commitment;
+
+        Ok(())
+    }
+}
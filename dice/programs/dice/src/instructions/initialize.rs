@@ -1,32 +1,62 @@
-use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{error::DiceError, state::HouseConfig, MAX_REFUND_TIMEOUT};
 
 #[derive(Accounts)]
-pub struct Initialize <'info>{
+pub struct Initialize<'info> {
     #[account(mut)]
-    pub house:Signer<'info>,
+    pub house: Signer<'info>,
+
     #[account(
-        mut, 
-        seeds=[b"vault",house.key().as_ref()],
+        init_if_needed,
+        payer = house,
+        space = 8 + HouseConfig::INIT_SPACE,
+        seeds = [b"config", house.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref()],
         bump
     )]
     pub vault: SystemAccount<'info>,
-    pub system_program:Program<'info, System>
+
+    pub system_program: Program<'info, System>,
 }
 
-impl <'info> Initialize<'info>{
+impl<'info> Initialize<'info> {
+    /// Funds the vault that pays out winning bets and sets how long players
+    /// wait before they can settle an unresolved bet. Can be called again to
+    /// top the vault up or change the timeout.
+    pub fn init(
+        &mut self,
+        bumps: &InitializeBumps,
+        amount: u64,
+        refund_timeout: u64,
+    ) -> Result<()> {
+        require!(
+            refund_timeout > 0 && refund_timeout <= MAX_REFUND_TIMEOUT,
+            DiceError::InvalidRefundTimeout
+        );
+
+        // Set field by field so calling this again keeps the commitment.
+        self.config.house = self.house.key();
+        self.config.refund_timeout = refund_timeout;
+        self.config.bump = bumps.config;
 
-    pub fn init(&mut self, amount:u64) -> Result<()> {
         let ctx = CpiContext::new(
             self.system_program.to_account_info(),
-            Transfer{
+            Transfer {
                 from: self.house.to_account_info(),
-                to:self.vault.to_account_info()
-            }
+                to: self.vault.to_account_info(),
+            },
         );
 
         transfer(ctx, amount)
-
+    }
 }
-
-}
-
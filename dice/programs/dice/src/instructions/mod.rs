@@ -1,9 +1,13 @@
+pub mod commit_seed;
 pub mod initialize;
 pub mod place_bet;
 pub mod resolve_bet;
 pub mod refund_bet;
+pub mod reveal_bet;
 
 pub use refund_bet::*;
+pub use reveal_bet::*;
+pub use commit_seed::*;
 pub use resolve_bet::*;
 pub use place_bet::*;
 pub use initialize::*;
@@ -1,48 +1,109 @@
-use crate::state::Bet;
-use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
 
+use crate::{
+    error::DiceError,
+    state::{Bet, HouseConfig},
+    MAX_ROLL, MIN_ROLL,
+};
 
 #[derive(Accounts)]
-#[instruction(seed:u128)]
-pub struct PlaceBet <'info>{
+#[instruction(seed: u128)]
+pub struct PlaceBet<'info> {
     #[account(mut)]
-    pub player:Signer<'info>,
+    pub player: Signer<'info>,
 
-    // CHECK: This is safe
-pub house: UncheckedAccount<'info>,
+    /// CHECK: Only used as the vault and config seed.
+    pub house: UncheckedAccount<'info>,
 
-#[account(
-    init,
-    payer= player,
-    space = 8+ Bet::INIT_SPACE,
-    seeds = [b"bet", vault.key().as_ref(), seed.to_le_bytes().as_ref()],
-    bump
-)]
-pub bet: Account<'info, Bet>,
+    // Required so every bet has a refund timeout to fall back on, and to
+    // reserve its payout.
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Bet::INIT_SPACE,
+        seeds = [b"bet", vault.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
 
     #[account(
-        mut, 
-        seeds=[b"vault",house.key().as_ref()],
+        mut,
+        seeds = [b"vault", house.key().as_ref()],
         bump
     )]
     pub vault: SystemAccount<'info>,
-    pub system_program:Program<'info, System>
+
+    pub system_program: Program<'info, System>,
 }
 
-impl <'info> PlaceBet <'info> {
-    pub fn PlaceBet(&mut self, bumps: &PlaceBetBumps, seed:u128, roll:u8, amount:u64) -> Result<()>{
-        self.bet.set_inner(Bet { player: self.player.key(), amount, slot: Clock::get()?.slot, seed, roll, bump: bumps.bet });
+impl<'info> PlaceBet<'info> {
+    pub fn create_bet(
+        &mut self,
+        bumps: &PlaceBetBumps,
+        seed: u128,
+        roll: u8,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, DiceError::InvalidAmount);
+        require!(
+            (MIN_ROLL..=MAX_ROLL).contains(&roll),
+            DiceError::InvalidRoll
+        );
+
+        self.bet.set_inner(Bet {
+            player: self.player.key(),
+            amount,
+            slot: Clock::get()?.slot,
+            seed,
+            roll,
+            bump: bumps.bet,
+            commitment: None,
+            entropy: [0; 32],
+        });
+
         Ok(())
     }
 
-    pub fn deposit(&mut self, amount:u64)-> Result<()>{
-  let ctx = CpiContext::new(
+    /// Ties the bet to the house's open commitment and records the player's
+    /// entropy, making it a commit-reveal bet. The player's entropy is
+    /// public, so the commitment must be the one the player signed against,
+    /// or the house could swap in a seed that loses once it sees the bet.
+    pub fn commit_bet(&mut self, entropy: [u8; 32], expected_commitment: [u8; 32]) -> Result<()> {
+        let commitment = self.config.commitment.ok_or(DiceError::NoCommitment)?;
+        require!(
+            commitment == expected_commitment,
+            DiceError::CommitmentMismatch
+        );
+
+        self.bet.commitment = Some(commitment);
+        self.bet.entropy = entropy;
+
+        Ok(())
+    }
+
+    /// Moves the stake into the vault, which must then hold enough to pay out
+    /// this bet and every other open bet if they all win.
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let ctx = CpiContext::new(
             self.system_program.to_account_info(),
-            Transfer{
+            Transfer {
                 from: self.player.to_account_info(),
-                to:self.vault.to_account_info()
-            }
+                to: self.vault.to_account_info(),
+            },
         );
 
-        transfer(ctx, amount)    }
-}
\ No newline at end of file
+        transfer(ctx, amount)?;
+
+        self.config.reserve(&self.bet, self.vault.lamports())
+    }
+}
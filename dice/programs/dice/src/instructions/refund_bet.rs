@@ -0,0 +1,72 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    error::DiceError,
+    state::{Bet, HouseConfig},
+};
+
+#[derive(Accounts)]
+pub struct RefundBet<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// CHECK: Only used as the vault and config seed.
+    pub house: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+
+    #[account(
+        mut,
+        close = player,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBet<'info> {
+    /// Hands the stake of a bet the house never resolved back to the player.
+    /// The bet account is closed to the player as well.
+    pub fn refund_bet(&mut self, bumps: &RefundBetBumps) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        require!(
+            self.bet.can_refund(slot, self.config.refund_timeout),
+            DiceError::BetNotTimedOut
+        );
+
+        // Frees the payout reserved for the bet; only the stake goes back.
+        self.config.release(&self.bet)?;
+
+        let house_key = self.house.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", house_key.as_ref(), &[bumps.vault]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: self.player.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        transfer(ctx, self.bet.amount)
+    }
+}
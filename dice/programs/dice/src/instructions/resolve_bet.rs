@@ -1,67 +1,123 @@
-use crate::state::Bet;
 use anchor_instruction_sysvar::Ed25519InstructionSignatures;
-use anchor_lang::{prelude::*, solana_program::{self, ed25519_program, sysvar::instructions::load_instruction_at_checked}, system_program::{transfer, Transfer}};
-use solana_program::example_mocks::solana_sdk::signature;
+use anchor_lang::{
+    prelude::*,
+    solana_program::{ed25519_program, sysvar::instructions::load_instruction_at_checked},
+    system_program::{transfer, Transfer},
+};
 
+use crate::{
+    error::DiceError,
+    state::{Bet, HouseConfig},
+};
 
 #[derive(Accounts)]
-pub struct ResolveBet <'info>{
+pub struct ResolveBet<'info> {
     #[account(mut)]
-    pub house:Signer<'info>,
+    pub house: Signer<'info>,
 
-    // CHECK: This is safe
-pub player: UncheckedAccount<'info>,
+    /// CHECK: Checked through `has_one` on the bet.
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
 
-#[account(
-    mut,
-    close= player,
-    has_one= player,
-    seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
-    bump = bet.bump
-)]
-pub bet: Account<'info, Bet>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+
+    #[account(
+        mut,
+        close = player,
+        has_one = player,
+        constraint = bet.commitment.is_none() @ DiceError::CommittedBet,
+        seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
 
     #[account(
-        mut, 
-        seeds=[b"vault",house.key().as_ref()],
+        mut,
+        seeds = [b"vault", house.key().as_ref()],
         bump
     )]
     pub vault: SystemAccount<'info>,
 
-#[account(
-    address= solana_program::sysvar::instructions::ID
-)]
+    /// CHECK: Address checked against the instructions sysvar.
+    #[account(
+        address = anchor_lang::solana_program::sysvar::instructions::ID
+    )]
     pub instruction_sysvar: AccountInfo<'info>,
-    pub system_program:Program<'info, System>
-}
 
-impl <'info> ResolveBet<'info> {
-    pub fn verify_ef25519_signature(&mut self, sig:&[u8])-> Result<()>{
-        let ix= load_instruction_at_checked(0, &self.instruction_sysvar.to_account_info())?;
+    pub system_program: Program<'info, System>,
+}
 
-        require_keys_eq!(ix.program_id, ed25519_program::ID);
+impl<'info> ResolveBet<'info> {
+    /// Checks that the first instruction of the transaction verified `sig`
+    /// as the house's ed25519 signature over the bet.
+    pub fn verify_ed25519_signature(&mut self, sig: &[u8]) -> Result<()> {
+        let ix = load_instruction_at_checked(0, &self.instruction_sysvar.to_account_info())?;
 
-        require_eq!(ix.accounts.len(), 0);
+        require_keys_eq!(
+            ix.program_id,
+            ed25519_program::ID,
+            DiceError::Ed25519Program
+        );
+        require_eq!(ix.accounts.len(), 0, DiceError::Ed25519Header);
 
         let signatures = Ed25519InstructionSignatures::unpack(&ix.data)?.0;
-
-        require_eq!(signatures.len(), 1);
+        require_eq!(signatures.len(), 1, DiceError::Ed25519Program);
 
         let signature = &signatures[0];
-
-        require!(signature.is_verifiable);
-
-        require_keys_eq!(signature.public_key.ok_or(err)?, self.house.key(), err);
-
-        require!(&signature.signature.ok_or(err)?.eq(sig), err);
-
-        require!(&signature.message.as_ref().ok_or(err).eq(&self.bet.to_slice()), err);
+        require!(signature.is_verifiable, DiceError::Ed25519Header);
+
+        require_keys_eq!(
+            signature.public_key.ok_or(DiceError::Ed25519Pubkey)?,
+            self.house.key(),
+            DiceError::Ed25519Pubkey
+        );
+        require!(
+            signature
+                .signature
+                .ok_or(DiceError::Ed25519Signature)?
+                .eq(sig),
+            DiceError::Ed25519Signature
+        );
+        require!(
+            signature
+                .message
+                .as_ref()
+                .ok_or(DiceError::Ed25519Signature)?
+                .eq(&self.bet.to_slice()),
+            DiceError::Ed25519Signature
+        );
 
         Ok(())
-
-
-
     }
 
-    pub 
+    /// Rolls a number from the verified signature and pays the player out of
+    /// the vault if it comes in under their roll. The bet is closed either
+    /// way, its rent going back to the player.
+    pub fn resolve_bet(&mut self, bumps: &ResolveBetBumps, sig: &[u8]) -> Result<()> {
+        let roll = Bet::roll_from(sig);
+        let payout = self.config.release(&self.bet)?;
+
+        if roll >= self.bet.roll {
+            return Ok(());
+        }
+
+        let house_key = self.house.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", house_key.as_ref(), &[bumps.vault]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: self.player.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        transfer(ctx, payout)
+    }
 }
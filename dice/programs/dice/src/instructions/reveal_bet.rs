@@ -0,0 +1,78 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    error::DiceError,
+    state::{Bet, HouseConfig},
+};
+
+#[derive(Accounts)]
+pub struct RevealBet<'info> {
+    #[account(mut)]
+    pub house: Signer<'info>,
+
+    /// CHECK: Checked through `has_one` on the bet.
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+
+    #[account(
+        mut,
+        close = player,
+        has_one = player,
+        constraint = bet.commitment.is_some() @ DiceError::NotCommittedBet,
+        seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RevealBet<'info> {
+    /// Resolves a commit-reveal bet with the house seed behind its
+    /// commitment. The commitment must already be replaced, otherwise anyone
+    /// who saw the seed could still bet against it.
+    pub fn reveal_bet(&mut self, bumps: &RevealBetBumps, house_seed: &[u8; 32]) -> Result<()> {
+        require!(
+            self.config.commitment != self.bet.commitment,
+            DiceError::CommitmentStillOpen
+        );
+
+        let roll = self.bet.roll_from_reveal(house_seed)?;
+        let payout = self.config.release(&self.bet)?;
+
+        if roll >= self.bet.roll {
+            return Ok(());
+        }
+
+        let house_key = self.house.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", house_key.as_ref(), &[bumps.vault]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: self.player.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        transfer(ctx, payout)
+    }
+}
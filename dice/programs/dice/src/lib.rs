@@ -1,3 +1,6 @@
+#![allow(unexpected_cfgs)]
+#![allow(deprecated)]
+
 pub mod constants;
 pub mod error;
 pub mod instructions;
@@ -15,7 +18,42 @@ declare_id!("DjmULeJg6gQrGTA1xM57VrfajSkKvjkAxyqmBQrJ1Fs2");
 pub mod dice {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        initialize::handler(ctx)
+    pub fn initialize(ctx: Context<Initialize>, amount: u64, refund_timeout: u64) -> Result<()> {
+        ctx.accounts.init(&ctx.bumps, amount, refund_timeout)
+    }
+
+    pub fn place_bet(ctx: Context<PlaceBet>, seed: u128, roll: u8, amount: u64) -> Result<()> {
+        ctx.accounts.create_bet(&ctx.bumps, seed, roll, amount)?;
+        ctx.accounts.deposit(amount)
+    }
+
+    pub fn place_committed_bet(
+        ctx: Context<PlaceBet>,
+        seed: u128,
+        roll: u8,
+        amount: u64,
+        entropy: [u8; 32],
+        expected_commitment: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.create_bet(&ctx.bumps, seed, roll, amount)?;
+        ctx.accounts.commit_bet(entropy, expected_commitment)?;
+        ctx.accounts.deposit(amount)
+    }
+
+    pub fn resolve_bet(ctx: Context<ResolveBet>, sig: Vec<u8>) -> Result<()> {
+        ctx.accounts.verify_ed25519_signature(&sig)?;
+        ctx.accounts.resolve_bet(&ctx.bumps, &sig)
+    }
+
+    pub fn commit_seed(ctx: Context<CommitSeed>, commitment: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.commit_seed(commitment)
+    }
+
+    pub fn reveal_bet(ctx: Context<RevealBet>, house_seed: [u8; 32]) -> Result<()> {
+        ctx.accounts.reveal_bet(&ctx.bumps, &house_seed)
+    }
+
+    pub fn refund_bet(ctx: Context<RefundBet>) -> Result<()> {
+        ctx.accounts.refund_bet(&ctx.bumps)
     }
 }
@@ -1,27 +1,117 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::hash::hash};
+
+use crate::{error::DiceError, HOUSE_EDGE_BPS};
+
+/// Per-house settings at `[b"config", house]`.
+#[account]
+#[derive(InitSpace)]
+pub struct HouseConfig {
+    pub house: Pubkey,
+    /// Slots after `Bet.slot` from which the player can settle a bet the
+    /// house has not resolved.
+    pub refund_timeout: u64,
+    /// Total payout of the open bets if they all won. The vault must hold at
+    /// least this much, so every open bet can be paid whichever way it goes.
+    pub owed: u64,
+    /// Hash of the secret seed that resolves commit-reveal bets placed from
+    /// now on. `None` while commit-reveal bets are closed.
+    pub commitment: Option<[u8; 32]>,
+    pub bump: u8,
+}
 
 #[account]
 #[derive(InitSpace)]
 pub struct Bet {
-        pub player: Pubkey,
-    pub amount:u64,
-    pub slot:u64,
+    pub player: Pubkey,
+    /// Lamports staked, held in the house vault until the bet is resolved.
+    pub amount: u64,
+    /// Slot the bet was placed in.
+    pub slot: u64,
     pub seed: u128,
-        pub roll: u8,
+    /// The bet wins when the resolved number is strictly below this.
+    pub roll: u8,
+    pub bump: u8,
+    /// Set on commit-reveal bets: the house commitment the bet was placed
+    /// under. Such bets are resolved by `reveal_bet` instead of a signature.
+    pub commitment: Option<[u8; 32]>,
+    /// Randomness the player adds to a commit-reveal bet.
+    pub entropy: [u8; 32],
+}
+
+impl HouseConfig {
+    /// Reserves the payout of a new bet and checks that a vault holding
+    /// `vault_balance` covers every open bet.
+    pub fn reserve(&mut self, bet: &Bet, vault_balance: u64) -> Result<()> {
+        self.owed = self
+            .owed
+            .checked_add(Bet::payout(bet.amount, bet.roll)?)
+            .ok_or(DiceError::Overflow)?;
 
-    pub bump:u8,
+        require!(vault_balance >= self.owed, DiceError::VaultTooSmall);
+
+        Ok(())
+    }
+
+    /// Releases the reserved payout of a bet that is being closed and
+    /// returns it.
+    pub fn release(&mut self, bet: &Bet) -> Result<u64> {
+        let payout = Bet::payout(bet.amount, bet.roll)?;
+        self.owed = self.owed.checked_sub(payout).ok_or(DiceError::Overflow)?;
+
+        Ok(payout)
+    }
 }
 
 impl Bet {
+    /// Whether the player can take the stake back at `slot`.
+    pub fn can_refund(&self, slot: u64, refund_timeout: u64) -> bool {
+        slot >= self.slot.saturating_add(refund_timeout)
+    }
+
+    /// The message the house signs to resolve the bet.
     pub fn to_slice(&self) -> Vec<u8> {
         let mut s: Vec<u8> = self.player.to_bytes().to_vec();
         s.extend_from_slice(&self.amount.to_le_bytes());
-                // s.extend_from_slice(&self.roll.to_le_bytes());
         s.extend_from_slice(&self.slot.to_le_bytes());
-                s.extend_from_slice(&self.seed.to_le_bytes());
-
-        s.extend_from_slice(&[self.roll,self.bump]);
+        s.extend_from_slice(&self.seed.to_le_bytes());
+        s.extend_from_slice(&[self.roll, self.bump]);
         s
+    }
+
+    /// Lamports paid out if the bet wins, stake included. A roll under `roll`
+    /// comes up with odds `(roll - 1) / 100`, so the fair payout is
+    /// `amount * 100 / (roll - 1)`, less the house edge.
+    pub fn payout(amount: u64, roll: u8) -> Result<u64> {
+        let payout = (amount as u128)
+            .checked_mul(10_000 - HOUSE_EDGE_BPS as u128)
+            .ok_or(DiceError::Overflow)?
+            / (roll as u128 - 1)
+            / 100;
+
+        u64::try_from(payout).map_err(|_| error!(DiceError::Overflow))
+    }
 
+    /// Checks `house_seed` against the commitment of a commit-reveal bet and
+    /// rolls from it together with the player's entropy and the bet seed.
+    pub fn roll_from_reveal(&self, house_seed: &[u8; 32]) -> Result<u8> {
+        let commitment = self.commitment.ok_or(DiceError::NotCommittedBet)?;
+        require!(
+            hash(house_seed).to_bytes() == commitment,
+            DiceError::InvalidReveal
+        );
+
+        Ok(Self::roll_from(
+            &[&house_seed[..], &self.entropy, &self.seed.to_le_bytes()].concat(),
+        ))
+    }
+
+    /// Hashes `entropy` down to a number from 1 to 100.
+    pub fn roll_from(entropy: &[u8]) -> u8 {
+        let hash = hash(entropy).to_bytes();
+
+        let lower = u128::from_le_bytes(hash[..16].try_into().unwrap());
+        let upper = u128::from_le_bytes(hash[16..].try_into().unwrap());
+
+        (lower.wrapping_add(upper) % 100) as u8 + 1
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,280 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import {
+  Ed25519Program,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
+import { assert } from "chai";
+import { createHash, randomBytes } from "crypto";
 import { Dice } from "../target/types/dice";
 
 describe("dice", () => {
-  // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
 
+  const provider = anchor.getProvider();
   const program = anchor.workspace.dice as Program<Dice>;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const house = Keypair.generate();
+  const player = Keypair.generate();
+  const seed = new anchor.BN(1);
+  const refundTimeout = 10;
+
+  const vault = PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), house.publicKey.toBuffer()],
+    program.programId
+  )[0];
+
+  const betAddress = (seed: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("bet"), vault.toBuffer(), seed.toArrayLike(Buffer, "le", 16)],
+      program.programId
+    )[0];
+
+  const bet = betAddress(seed);
+
+  const config = PublicKey.findProgramAddressSync(
+    [Buffer.from("config"), house.publicKey.toBuffer()],
+    program.programId
+  )[0];
+
+  // Mirrors `Bet::payout`: stake * (1 - edge) * 100 / (roll - 1).
+  const payout = (amount: number, roll: number) =>
+    Math.floor(Math.floor((amount * 9_850) / (roll - 1)) / 100);
+
+  const confirm = async (signature: string): Promise<string> => {
+    const block = await provider.connection.getLatestBlockhash();
+    await provider.connection.confirmTransaction({ signature, ...block });
+    return signature;
+  };
+
+  it("Airdrop", async () => {
+    await Promise.all(
+      [house, player].map((k) =>
+        provider.connection
+          .requestAirdrop(k.publicKey, 100 * LAMPORTS_PER_SOL)
+          .then(confirm)
+      )
+    );
+  });
+
+  it("Initialize the house vault", async () => {
+    await program.methods
+      .initialize(new anchor.BN(50 * LAMPORTS_PER_SOL), new anchor.BN(refundTimeout))
+      .accountsPartial({ house: house.publicKey, vault })
+      .signers([house])
+      .rpc()
+      .then(confirm);
+
+    const balance = await provider.connection.getBalance(vault);
+    assert.equal(balance, 50 * LAMPORTS_PER_SOL);
+  });
+
+  it("Reject a roll out of range", async () => {
+    try {
+      await program.methods
+        .placeBet(new anchor.BN(99), 100, new anchor.BN(LAMPORTS_PER_SOL))
+        .accountsPartial({ player: player.publicKey, house: house.publicKey, vault })
+        .signers([player])
+        .rpc();
+      assert.fail("place_bet should reject a roll above MAX_ROLL");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidRoll");
+    }
+  });
+
+  it("Place a bet", async () => {
+    await program.methods
+      .placeBet(seed, 50, new anchor.BN(LAMPORTS_PER_SOL / 100))
+      .accountsPartial({ player: player.publicKey, house: house.publicKey, vault, bet })
+      .signers([player])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.bet.fetch(bet);
+    assert.equal(state.roll, 50);
+
+    // The vault keeps the payout of every open bet reserved.
+    const { owed } = await program.account.houseConfig.fetch(config);
+    assert.equal(owed.toNumber(), payout(LAMPORTS_PER_SOL / 100, 50));
+  });
+
+  it("Resolve the bet with a house signature", async () => {
+    // The signed message is `Bet::to_slice`, the bet account's data from
+    // `player` through `bump`.
+    const account = await provider.connection.getAccountInfo(bet);
+    const message = account.data.subarray(8, 8 + 66);
+
+    const sigIx = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: house.secretKey,
+      message,
+    });
+    // Signature sits after the 2 byte header, the 14 byte offsets and the key.
+    const sig = Buffer.from(sigIx.data.subarray(16 + 32, 16 + 32 + 64));
+
+    const resolveIx = await program.methods
+      .resolveBet(sig)
+      .accountsPartial({
+        house: house.publicKey,
+        player: player.publicKey,
+        bet,
+        vault,
+        instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .instruction();
+
+    const tx = new anchor.web3.Transaction().add(sigIx).add(resolveIx);
+    await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [house]);
+
+    assert.isNull(await provider.connection.getAccountInfo(bet));
+
+    const { owed } = await program.account.houseConfig.fetch(config);
+    assert.equal(owed.toNumber(), 0);
+  });
+
+  it("Refund a bet the house never resolved", async () => {
+    const refundSeed = new anchor.BN(2);
+    const refundBet = betAddress(refundSeed);
+    const accounts = {
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      bet: refundBet,
+    };
+
+    await program.methods
+      .placeBet(refundSeed, 20, new anchor.BN(LAMPORTS_PER_SOL / 100))
+      .accountsPartial(accounts)
+      .signers([player])
+      .rpc()
+      .then(confirm);
+
+    try {
+      await program.methods
+        .refundBet()
+        .accountsPartial(accounts)
+        .signers([player])
+        .rpc();
+      assert.fail("refund_bet should wait for the refund timeout");
+    } catch (err) {
+      assert.include(err.toString(), "BetNotTimedOut");
+    }
+
+    const { slot } = await program.account.bet.fetch(refundBet);
+    while ((await provider.connection.getSlot()) < slot.toNumber() + refundTimeout) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+
+    const before = await provider.connection.getBalance(vault);
+
+    await program.methods
+      .refundBet()
+      .accountsPartial(accounts)
+      .signers([player])
+      .rpc()
+      .then(confirm);
+
+    assert.equal(before - (await provider.connection.getBalance(vault)), LAMPORTS_PER_SOL / 100);
+
+    const { owed } = await program.account.houseConfig.fetch(config);
+    assert.equal(owed.toNumber(), 0);
+    assert.isNull(await provider.connection.getAccountInfo(refundBet));
+  });
+
+  it("Resolve a commit-reveal bet", async () => {
+    const houseSeed = randomBytes(32);
+    const commitment = createHash("sha256").update(houseSeed).digest();
+    const entropy = randomBytes(32);
+
+    const revealSeed = new anchor.BN(3);
+    const revealBet = betAddress(revealSeed);
+    const accounts = {
+      house: house.publicKey,
+      player: player.publicKey,
+      vault,
+      bet: revealBet,
+    };
+
+    await program.methods
+      .commitSeed([...commitment])
+      .accountsPartial({ house: house.publicKey })
+      .signers([house])
+      .rpc()
+      .then(confirm);
+
+    // A bet only goes through against the commitment the player saw.
+    try {
+      await program.methods
+        .placeCommittedBet(
+          revealSeed,
+          50,
+          new anchor.BN(LAMPORTS_PER_SOL / 100),
+          [...entropy],
+          [...randomBytes(32)]
+        )
+        .accountsPartial(accounts)
+        .signers([player])
+        .rpc();
+      assert.fail("place_committed_bet should reject a swapped commitment");
+    } catch (err) {
+      assert.include(err.toString(), "CommitmentMismatch");
+    }
+
+    await program.methods
+      .placeCommittedBet(
+        revealSeed,
+        50,
+        new anchor.BN(LAMPORTS_PER_SOL / 100),
+        [...entropy],
+        [...commitment]
+      )
+      .accountsPartial(accounts)
+      .signers([player])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.bet.fetch(revealBet);
+    assert.deepEqual(state.commitment, [...commitment]);
+
+    try {
+      await program.methods
+        .revealBet([...houseSeed])
+        .accountsPartial(accounts)
+        .signers([house])
+        .rpc();
+      assert.fail("reveal_bet should wait for the commitment to be retired");
+    } catch (err) {
+      assert.include(err.toString(), "CommitmentStillOpen");
+    }
+
+    // Close commit-reveal betting before revealing the seed.
+    await program.methods
+      .commitSeed(null)
+      .accountsPartial({ house: house.publicKey })
+      .signers([house])
+      .rpc()
+      .then(confirm);
+
+    try {
+      await program.methods
+        .revealBet([...randomBytes(32)])
+        .accountsPartial(accounts)
+        .signers([house])
+        .rpc();
+      assert.fail("reveal_bet should reject a seed that does not match");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidReveal");
+    }
+
+    await program.methods
+      .revealBet([...houseSeed])
+      .accountsPartial(accounts)
+      .signers([house])
+      .rpc()
+      .then(confirm);
+
+    assert.isNull(await provider.connection.getAccountInfo(revealBet));
   });
 });
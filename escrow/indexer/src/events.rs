@@ -0,0 +1,228 @@
+//! Pulls the escrow's events out of a transaction's log messages.
+//!
+//! Takes are often routed through other programs, so the escrow can run as an
+//! inner instruction next to programs that log `Program data:` lines of their
+//! own. A data line is only decoded when the escrow is the innermost program
+//! running at that point of the log.
+
+use anchor_lang::{prelude::*, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use escrow::{
+    BundleMade, BundleRefunded, BundleTaken, OfferAmended, OfferMade, OfferRefunded, OfferTaken,
+};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowEvent {
+    Made(OfferMade),
+    Taken(OfferTaken),
+    Amended(OfferAmended),
+    Refunded(OfferRefunded),
+    BundleMade(BundleMade),
+    BundleTaken(BundleTaken),
+    BundleRefunded(BundleRefunded),
+}
+
+impl EscrowEvent {
+    /// Address of the escrow or bundle account the event is about.
+    pub fn escrow(&self) -> Pubkey {
+        match self {
+            EscrowEvent::Made(event) => event.escrow,
+            EscrowEvent::Taken(event) => event.escrow,
+            EscrowEvent::Amended(event) => event.escrow,
+            EscrowEvent::Refunded(event) => event.escrow,
+            EscrowEvent::BundleMade(event) => event.bundle,
+            EscrowEvent::BundleTaken(event) => event.bundle,
+            EscrowEvent::BundleRefunded(event) => event.bundle,
+        }
+    }
+
+    /// Decodes an event from its discriminator and borsh body.
+    pub fn decode(data: &[u8]) -> Option<EscrowEvent> {
+        fn body<T: Discriminator + AnchorDeserialize>(data: &[u8]) -> Option<T> {
+            let mut rest = data.strip_prefix(T::DISCRIMINATOR)?;
+            T::deserialize(&mut rest).ok()
+        }
+
+        let discriminator = data.get(..8)?;
+
+        if discriminator == OfferMade::DISCRIMINATOR {
+            body(data).map(EscrowEvent::Made)
+        } else if discriminator == OfferTaken::DISCRIMINATOR {
+            body(data).map(EscrowEvent::Taken)
+        } else if discriminator == OfferAmended::DISCRIMINATOR {
+            body(data).map(EscrowEvent::Amended)
+        } else if discriminator == OfferRefunded::DISCRIMINATOR {
+            body(data).map(EscrowEvent::Refunded)
+        } else if discriminator == BundleMade::DISCRIMINATOR {
+            body(data).map(EscrowEvent::BundleMade)
+        } else if discriminator == BundleTaken::DISCRIMINATOR {
+            body(data).map(EscrowEvent::BundleTaken)
+        } else if discriminator == BundleRefunded::DISCRIMINATOR {
+            body(data).map(EscrowEvent::BundleRefunded)
+        } else {
+            None
+        }
+    }
+}
+
+/// What a runtime log line does to the stack of running programs.
+enum Frame<'a> {
+    Enter(&'a str),
+    Leave,
+}
+
+impl<'a> Frame<'a> {
+    /// Reads `Program <id> invoke [n]`, `Program <id> success` and
+    /// `Program <id> failed: <reason>`; every other line leaves the stack alone.
+    fn parse(line: &'a str) -> Option<Frame<'a>> {
+        let mut words = line.strip_prefix("Program ")?.split(' ');
+        let id = words.next()?;
+
+        match words.next()? {
+            "invoke" => Some(Frame::Enter(id)),
+            "success" | "failed:" => Some(Frame::Leave),
+            _ => None,
+        }
+    }
+}
+
+/// The escrow events `program_id` logged in one transaction, oldest first.
+pub fn parse_logs<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<EscrowEvent> {
+    let program_id = program_id.to_string();
+    // One flag per running program, set where that program is the escrow.
+    let mut running: Vec<bool> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs.iter().map(AsRef::as_ref) {
+        let Some(data) = line.strip_prefix(PROGRAM_DATA) else {
+            match Frame::parse(line) {
+                Some(Frame::Enter(id)) => running.push(id == program_id),
+                Some(Frame::Leave) => {
+                    running.pop();
+                }
+                None => {}
+            }
+
+            continue;
+        };
+
+        if running.last() != Some(&true) {
+            continue;
+        }
+
+        let event = STANDARD
+            .decode(data)
+            .ok()
+            .and_then(|bytes| EscrowEvent::decode(&bytes));
+
+        events.extend(event);
+    }
+
+    events
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use escrow::Leg;
+
+    use super::*;
+
+    /// The log line Anchor's `emit!` writes for `event`.
+    fn emitted<T: Discriminator + AnchorSerialize>(event: &T) -> String {
+        let mut bytes = T::DISCRIMINATOR.to_vec();
+        event.serialize(&mut bytes).unwrap();
+        format!("Program data: {}", STANDARD.encode(bytes))
+    }
+
+    pub(crate) fn made(escrow: Pubkey, amount: u64, receive: u64) -> OfferMade {
+        OfferMade {
+            escrow,
+            seed: 1,
+            maker: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            amount,
+            receive,
+            timestamp: 100,
+        }
+    }
+
+    #[test]
+    fn keeps_a_routed_take_and_drops_the_router_events() {
+        let router = Pubkey::new_unique();
+        let made = made(Pubkey::new_unique(), 500, 700);
+        let taken = OfferTaken {
+            escrow: made.escrow,
+            seed: made.seed,
+            maker: made.maker,
+            taker: Pubkey::new_unique(),
+            mint_a: made.mint_a,
+            mint_b: made.mint_b,
+            amount: 500,
+            paid: 700,
+            remaining: 0,
+            timestamp: 120,
+        };
+
+        // The router re-logs the take as its own receipt once the escrow returns.
+        let logs = vec![
+            format!("Program {router} invoke [1]"),
+            "Program log: Instruction: Route".to_string(),
+            emitted(&made),
+            format!("Program {} invoke [2]", escrow::ID),
+            "Program log: Instruction: Take".to_string(),
+            emitted(&taken),
+            format!(
+                "Program {} consumed 41000 of 180000 compute units",
+                escrow::ID
+            ),
+            format!("Program {} success", escrow::ID),
+            emitted(&taken),
+            format!("Program {router} success"),
+        ];
+
+        assert_eq!(
+            parse_logs(&escrow::ID, &logs),
+            vec![EscrowEvent::Taken(taken)]
+        );
+    }
+
+    #[test]
+    fn decodes_bundle_legs_and_skips_unknown_data() {
+        let bundle = BundleMade {
+            bundle: Pubkey::new_unique(),
+            seed: 7,
+            maker: Pubkey::new_unique(),
+            offered: vec![
+                Leg {
+                    mint: Pubkey::new_unique(),
+                    amount: 10,
+                },
+                Leg {
+                    mint: Pubkey::new_unique(),
+                    amount: 20,
+                },
+            ],
+            requested: vec![Leg {
+                mint: Pubkey::new_unique(),
+                amount: 30,
+            }],
+            timestamp: 100,
+        };
+
+        let logs = vec![
+            format!("Program {} invoke [1]", escrow::ID),
+            "Program data: not base64".to_string(),
+            format!("Program data: {}", STANDARD.encode([0u8; 16])),
+            emitted(&bundle),
+            format!("Program {} success", escrow::ID),
+        ];
+
+        assert_eq!(
+            parse_logs(&escrow::ID, &logs),
+            vec![EscrowEvent::BundleMade(bundle)]
+        );
+    }
+}
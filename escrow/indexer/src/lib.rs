@@ -0,0 +1,34 @@
+//! Rebuilds the escrow's offers from its `OfferMade`, `OfferTaken`,
+//! `OfferAmended` and `OfferRefunded` events, and its bundles from the
+//! `Bundle*` ones, and keeps them in a local SQLite database.
+//!
+//! `sync` pages through the program's transactions since the stored cursor,
+//! so it can be run once or in a loop against any RPC node, including a
+//! local `solana-test-validator`.
+
+pub mod events;
+pub mod rpc;
+pub mod store;
+
+pub use events::*;
+pub use rpc::*;
+pub use store::*;
+
+use anchor_lang::prelude::Pubkey;
+
+/// Applies every transaction of `program_id` newer than the store's cursor
+/// and returns how many events were indexed.
+pub fn sync(rpc: &RpcClient, store: &mut OfferStore, program_id: &Pubkey) -> anyhow::Result<usize> {
+    let cursor = store.cursor()?;
+    let mut indexed = 0;
+
+    for signature in rpc.signatures_since(program_id, cursor.as_deref())? {
+        let logs = rpc.transaction_logs(&signature)?;
+        let events = parse_logs(program_id, &logs);
+
+        store.apply(&signature, &events)?;
+        indexed += events.len();
+    }
+
+    Ok(indexed)
+}
@@ -0,0 +1,70 @@
+use std::{str::FromStr, thread, time::Duration};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{bail, Context, Result};
+use escrow_indexer::{sync, OfferStatus, OfferStore, RpcClient};
+
+const USAGE: &str =
+    "usage: escrow-indexer [--url <rpc>] [--db <path>] [--program <id>] [--poll <seconds>]";
+
+struct Args {
+    url: String,
+    db: String,
+    program_id: Pubkey,
+    poll: Option<u64>,
+}
+
+impl Args {
+    fn parse() -> Result<Args> {
+        let mut args = Args {
+            url: "http://127.0.0.1:8899".to_string(),
+            db: "escrow-offers.db".to_string(),
+            program_id: escrow::ID,
+            poll: None,
+        };
+
+        let mut argv = std::env::args().skip(1);
+
+        while let Some(flag) = argv.next() {
+            let mut value = || {
+                argv.next()
+                    .with_context(|| format!("{flag} needs a value\n{USAGE}"))
+            };
+
+            match flag.as_str() {
+                "--url" => args.url = value()?,
+                "--db" => args.db = value()?,
+                "--program" => args.program_id = Pubkey::from_str(&value()?)?,
+                "--poll" => args.poll = Some(value()?.parse()?),
+                _ => bail!("unknown argument {flag}\n{USAGE}"),
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse()?;
+    let rpc = RpcClient::new(&args.url);
+    let mut store = OfferStore::open(&args.db)?;
+
+    loop {
+        let indexed = sync(&rpc, &mut store, &args.program_id)?;
+
+        let open = store.offers(OfferStatus::Open)?.len();
+        let filled = store.offers(OfferStatus::Filled)?.len();
+        let refunded = store.offers(OfferStatus::Refunded)?.len();
+        let bundles = store.bundles(OfferStatus::Open)?.len();
+        println!(
+            "indexed {indexed} events: {open} open, {filled} filled, {refunded} refunded, \
+             {bundles} open bundles"
+        );
+
+        let Some(seconds) = args.poll else {
+            return Ok(());
+        };
+
+        thread::sleep(Duration::from_secs(seconds));
+    }
+}
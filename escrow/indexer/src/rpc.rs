@@ -0,0 +1,104 @@
+//! The two JSON-RPC calls the indexer needs, over plain HTTP.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+
+/// Most signatures `getSignaturesForAddress` returns per page.
+const PAGE_LIMIT: usize = 1_000;
+
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> RpcClient {
+        RpcClient {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .with_context(|| format!("{method} request to {} failed", self.url))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{method} failed: {error}"));
+        }
+
+        Ok(response["result"].clone())
+    }
+
+    /// Signatures of successful transactions that touched `address` after
+    /// `until`, oldest first. With no `until`, the whole history is returned.
+    pub fn signatures_since(&self, address: &Pubkey, until: Option<&str>) -> Result<Vec<String>> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+
+        loop {
+            let page = self.call(
+                "getSignaturesForAddress",
+                json!([
+                    address.to_string(),
+                    {
+                        "limit": PAGE_LIMIT,
+                        "before": before,
+                        "until": until,
+                        "commitment": "confirmed",
+                    }
+                ]),
+            )?;
+
+            let page = page.as_array().context("signature page is not an array")?;
+
+            for entry in page {
+                let signature = entry["signature"]
+                    .as_str()
+                    .context("signature entry without a signature")?;
+
+                if entry["err"].is_null() {
+                    signatures.push(signature.to_string());
+                }
+
+                before = Some(signature.to_string());
+            }
+
+            if page.len() < PAGE_LIMIT {
+                break;
+            }
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    /// Log messages of a confirmed transaction.
+    pub fn transaction_logs(&self, signature: &str) -> Result<Vec<String>> {
+        let transaction = self.call(
+            "getTransaction",
+            json!([
+                signature,
+                {
+                    "encoding": "json",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0,
+                }
+            ]),
+        )?;
+
+        let logs = transaction["meta"]["logMessages"]
+            .as_array()
+            .with_context(|| format!("transaction {signature} has no logs"))?;
+
+        Ok(logs
+            .iter()
+            .filter_map(|line| line.as_str().map(str::to_string))
+            .collect())
+    }
+}
@@ -0,0 +1,696 @@
+//! Local SQLite table of escrow offers.
+//!
+//! Every `OfferMade` opens a row, `OfferTaken` adds to its fills and marks it
+//! filled once nothing is left, `OfferAmended` resets it to the new terms
+//! with nothing filled, as the program does, and `OfferRefunded` closes it.
+//! A refund whose `OfferMade` is not in the indexed history still gets a
+//! closed row, opened by the refund's own transaction and with `receive`
+//! left at zero since the event does not carry it. An escrow
+//! address is reused when a maker opens a new offer with the same seed, so
+//! takes and refunds apply to the open row of that address and older rows
+//! are kept as history. Amounts are `u64` stored in SQLite's signed
+//! `INTEGER`, bit for bit.
+//!
+//! Bundles go to their own `bundles` table, with one `bundle_legs` row per
+//! offered or requested leg. They follow the same rules, except that a bundle
+//! is always filled by a single take.
+
+use std::{fmt, path::Path, str::FromStr};
+
+use anchor_lang::prelude::Pubkey;
+use escrow::Leg;
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+
+use crate::EscrowEvent;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS offers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    escrow TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    maker TEXT NOT NULL,
+    mint_a TEXT NOT NULL,
+    mint_b TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    receive INTEGER NOT NULL,
+    filled INTEGER NOT NULL DEFAULT 0,
+    received INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL CHECK (status IN ('open', 'filled', 'refunded')),
+    opened_at INTEGER NOT NULL,
+    closed_at INTEGER,
+    signature TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS offers_escrow ON offers (escrow, status);
+
+CREATE TABLE IF NOT EXISTS bundles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bundle TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    maker TEXT NOT NULL,
+    taker TEXT,
+    status TEXT NOT NULL CHECK (status IN ('open', 'filled', 'refunded')),
+    opened_at INTEGER NOT NULL,
+    closed_at INTEGER,
+    signature TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS bundles_bundle ON bundles (bundle, status);
+
+CREATE TABLE IF NOT EXISTS bundle_legs (
+    bundle_id INTEGER NOT NULL REFERENCES bundles (id),
+    side TEXT NOT NULL CHECK (side IN ('offered', 'requested')),
+    mint TEXT NOT NULL,
+    amount INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS bundle_legs_bundle ON bundle_legs (bundle_id);
+
+CREATE TABLE IF NOT EXISTS cursor (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    signature TEXT NOT NULL
+);
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OfferStatus {
+    Open,
+    Filled,
+    Refunded,
+}
+
+impl OfferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OfferStatus::Open => "open",
+            OfferStatus::Filled => "filled",
+            OfferStatus::Refunded => "refunded",
+        }
+    }
+}
+
+impl fmt::Display for OfferStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OfferStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(OfferStatus::Open),
+            "filled" => Ok(OfferStatus::Filled),
+            "refunded" => Ok(OfferStatus::Refunded),
+            other => Err(format!("unknown offer status {other}")),
+        }
+    }
+}
+
+/// One row of the `offers` table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Offer {
+    pub escrow: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount: u64,
+    pub receive: u64,
+    pub filled: u64,
+    pub received: u64,
+    pub status: OfferStatus,
+    pub opened_at: i64,
+    pub closed_at: Option<i64>,
+    /// Transaction that opened the offer.
+    pub signature: String,
+}
+
+impl Offer {
+    fn from_row(row: &Row) -> rusqlite::Result<Offer> {
+        Ok(Offer {
+            escrow: key(row, 0)?,
+            seed: row.get::<_, i64>(1)? as u64,
+            maker: key(row, 2)?,
+            mint_a: key(row, 3)?,
+            mint_b: key(row, 4)?,
+            amount: row.get::<_, i64>(5)? as u64,
+            receive: row.get::<_, i64>(6)? as u64,
+            filled: row.get::<_, i64>(7)? as u64,
+            received: row.get::<_, i64>(8)? as u64,
+            status: status(row, 9)?,
+            opened_at: row.get(10)?,
+            closed_at: row.get(11)?,
+            signature: row.get(12)?,
+        })
+    }
+}
+
+/// One row of the `bundles` table, with its legs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bundle {
+    pub bundle: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub taker: Option<Pubkey>,
+    pub offered: Vec<Leg>,
+    pub requested: Vec<Leg>,
+    pub status: OfferStatus,
+    pub opened_at: i64,
+    pub closed_at: Option<i64>,
+    /// Transaction that opened the bundle.
+    pub signature: String,
+}
+
+impl Bundle {
+    /// Reads a row of `BUNDLE_COLUMNS` along with its id; the legs are
+    /// loaded separately.
+    fn from_row(row: &Row) -> rusqlite::Result<(i64, Bundle)> {
+        let taker: Option<String> = row.get(4)?;
+
+        let bundle = Bundle {
+            bundle: key(row, 1)?,
+            seed: row.get::<_, i64>(2)? as u64,
+            maker: key(row, 3)?,
+            taker: taker.map(|_| key(row, 4)).transpose()?,
+            offered: Vec::new(),
+            requested: Vec::new(),
+            status: status(row, 5)?,
+            opened_at: row.get(6)?,
+            closed_at: row.get(7)?,
+            signature: row.get(8)?,
+        };
+
+        Ok((row.get(0)?, bundle))
+    }
+}
+
+fn key(row: &Row, index: usize) -> rusqlite::Result<Pubkey> {
+    let text: String = row.get(index)?;
+    Pubkey::from_str(&text).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, e.into())
+    })
+}
+
+fn status(row: &Row, index: usize) -> rusqlite::Result<OfferStatus> {
+    let text: String = row.get(index)?;
+    text.parse().map_err(|e: String| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, e.into())
+    })
+}
+
+const OFFER_COLUMNS: &str = "escrow, seed, maker, mint_a, mint_b, amount, receive, filled, \
+     received, status, opened_at, closed_at, signature";
+
+pub struct OfferStore {
+    conn: Connection,
+}
+
+impl OfferStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> rusqlite::Result<OfferStore> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> rusqlite::Result<OfferStore> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<OfferStore> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(OfferStore { conn })
+    }
+
+    /// Newest transaction already applied, if any.
+    pub fn cursor(&self) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row("SELECT signature FROM cursor WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()
+    }
+
+    /// Applies the events of one transaction and moves the cursor past it,
+    /// atomically. Pass transactions oldest first.
+    pub fn apply(&mut self, signature: &str, events: &[EscrowEvent]) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+
+        for event in events {
+            match event {
+                EscrowEvent::Made(e) => {
+                    tx.execute(
+                        "INSERT INTO offers (escrow, seed, maker, mint_a, mint_b, amount, receive, \
+                         status, opened_at, signature) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![
+                            e.escrow.to_string(),
+                            e.seed as i64,
+                            e.maker.to_string(),
+                            e.mint_a.to_string(),
+                            e.mint_b.to_string(),
+                            e.amount as i64,
+                            e.receive as i64,
+                            OfferStatus::Open.as_str(),
+                            e.timestamp,
+                            signature,
+                        ],
+                    )?;
+                }
+                EscrowEvent::Taken(e) => {
+                    let status = if e.remaining == 0 {
+                        OfferStatus::Filled
+                    } else {
+                        OfferStatus::Open
+                    };
+
+                    tx.execute(
+                        "UPDATE offers SET filled = filled + ?2, received = received + ?3, \
+                         status = ?4, closed_at = CASE WHEN ?4 = 'open' THEN NULL ELSE ?5 END \
+                         WHERE escrow = ?1 AND status = 'open'",
+                        params![
+                            e.escrow.to_string(),
+                            e.amount as i64,
+                            e.paid as i64,
+                            status.as_str(),
+                            e.timestamp,
+                        ],
+                    )?;
+                }
+                EscrowEvent::Amended(e) => {
+                    tx.execute(
+                        "UPDATE offers SET amount = ?2, receive = ?3, filled = 0, received = 0 \
+                         WHERE escrow = ?1 AND status = 'open'",
+                        params![e.escrow.to_string(), e.amount as i64, e.receive as i64],
+                    )?;
+                }
+                EscrowEvent::Refunded(e) => {
+                    let closed = tx.execute(
+                        "UPDATE offers SET status = 'refunded', closed_at = ?2 \
+                         WHERE escrow = ?1 AND status = 'open'",
+                        params![e.escrow.to_string(), e.timestamp],
+                    )?;
+
+                    if closed == 0 {
+                        tx.execute(
+                            "INSERT INTO offers (escrow, seed, maker, mint_a, mint_b, amount, \
+                             receive, status, opened_at, closed_at, signature) \
+                             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, 'refunded', ?7, ?7, ?8)",
+                            params![
+                                e.escrow.to_string(),
+                                e.seed as i64,
+                                e.maker.to_string(),
+                                e.mint_a.to_string(),
+                                e.mint_b.to_string(),
+                                e.amount as i64,
+                                e.timestamp,
+                                signature,
+                            ],
+                        )?;
+                    }
+                }
+                EscrowEvent::BundleMade(e) => {
+                    tx.execute(
+                        "INSERT INTO bundles (bundle, seed, maker, status, opened_at, signature) \
+                         VALUES (?1, ?2, ?3, 'open', ?4, ?5)",
+                        params![
+                            e.bundle.to_string(),
+                            e.seed as i64,
+                            e.maker.to_string(),
+                            e.timestamp,
+                            signature,
+                        ],
+                    )?;
+
+                    let id = tx.last_insert_rowid();
+                    insert_legs(&tx, id, "offered", &e.offered)?;
+                    insert_legs(&tx, id, "requested", &e.requested)?;
+                }
+                EscrowEvent::BundleTaken(e) => {
+                    tx.execute(
+                        "UPDATE bundles SET taker = ?2, status = 'filled', closed_at = ?3 \
+                         WHERE bundle = ?1 AND status = 'open'",
+                        params![e.bundle.to_string(), e.taker.to_string(), e.timestamp],
+                    )?;
+                }
+                EscrowEvent::BundleRefunded(e) => {
+                    let closed = tx.execute(
+                        "UPDATE bundles SET status = 'refunded', closed_at = ?2 \
+                         WHERE bundle = ?1 AND status = 'open'",
+                        params![e.bundle.to_string(), e.timestamp],
+                    )?;
+
+                    if closed == 0 {
+                        tx.execute(
+                            "INSERT INTO bundles (bundle, seed, maker, status, opened_at, \
+                             closed_at, signature) VALUES (?1, ?2, ?3, 'refunded', ?4, ?4, ?5)",
+                            params![
+                                e.bundle.to_string(),
+                                e.seed as i64,
+                                e.maker.to_string(),
+                                e.timestamp,
+                                signature,
+                            ],
+                        )?;
+
+                        insert_legs(&tx, tx.last_insert_rowid(), "offered", &e.offered)?;
+                    }
+                }
+            }
+        }
+
+        tx.execute(
+            "INSERT INTO cursor (id, signature) VALUES (0, ?1) \
+             ON CONFLICT (id) DO UPDATE SET signature = excluded.signature",
+            params![signature],
+        )?;
+
+        tx.commit()
+    }
+
+    /// Every offer in `status`, oldest first.
+    pub fn offers(&self, status: OfferStatus) -> rusqlite::Result<Vec<Offer>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {OFFER_COLUMNS} FROM offers WHERE status = ?1 ORDER BY id"
+        ))?;
+
+        let rows = stmt.query_map(params![status.as_str()], Offer::from_row)?;
+        rows.collect()
+    }
+
+    /// The most recent offer at `escrow`.
+    pub fn offer(&self, escrow: &Pubkey) -> rusqlite::Result<Option<Offer>> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {OFFER_COLUMNS} FROM offers WHERE escrow = ?1 ORDER BY id DESC LIMIT 1"
+                ),
+                params![escrow.to_string()],
+                Offer::from_row,
+            )
+            .optional()
+    }
+
+    /// Every bundle in `status`, oldest first.
+    pub fn bundles(&self, status: OfferStatus) -> rusqlite::Result<Vec<Bundle>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {BUNDLE_COLUMNS} FROM bundles WHERE status = ?1 ORDER BY id"
+        ))?;
+
+        let rows = stmt.query_map(params![status.as_str()], Bundle::from_row)?;
+
+        rows.map(|row| row.and_then(|(id, bundle)| self.with_legs(id, bundle)))
+            .collect()
+    }
+
+    /// The most recent bundle at `bundle`.
+    pub fn bundle(&self, bundle: &Pubkey) -> rusqlite::Result<Option<Bundle>> {
+        let row = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {BUNDLE_COLUMNS} FROM bundles WHERE bundle = ?1 ORDER BY id DESC LIMIT 1"
+                ),
+                params![bundle.to_string()],
+                Bundle::from_row,
+            )
+            .optional()?;
+
+        row.map(|(id, bundle)| self.with_legs(id, bundle))
+            .transpose()
+    }
+
+    fn with_legs(&self, id: i64, mut bundle: Bundle) -> rusqlite::Result<Bundle> {
+        let mut stmt = self.conn.prepare(
+            "SELECT side, mint, amount FROM bundle_legs WHERE bundle_id = ?1 ORDER BY rowid",
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+
+        while let Some(row) = rows.next()? {
+            let side: String = row.get(0)?;
+            let leg = Leg {
+                mint: key(row, 1)?,
+                amount: row.get::<_, i64>(2)? as u64,
+            };
+
+            match side.as_str() {
+                "offered" => bundle.offered.push(leg),
+                _ => bundle.requested.push(leg),
+            }
+        }
+
+        Ok(bundle)
+    }
+}
+
+const BUNDLE_COLUMNS: &str =
+    "id, bundle, seed, maker, taker, status, opened_at, closed_at, signature";
+
+fn insert_legs(tx: &Transaction, bundle_id: i64, side: &str, legs: &[Leg]) -> rusqlite::Result<()> {
+    for leg in legs {
+        tx.execute(
+            "INSERT INTO bundle_legs (bundle_id, side, mint, amount) VALUES (?1, ?2, ?3, ?4)",
+            params![bundle_id, side, leg.mint.to_string(), leg.amount as i64],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use escrow::{
+        BundleMade, BundleRefunded, BundleTaken, OfferAmended, OfferRefunded, OfferTaken,
+    };
+
+    use super::*;
+    use crate::events::tests::made;
+
+    fn taken(made: &escrow::OfferMade, amount: u64, paid: u64, remaining: u64) -> OfferTaken {
+        OfferTaken {
+            escrow: made.escrow,
+            seed: made.seed,
+            maker: made.maker,
+            taker: Pubkey::new_unique(),
+            mint_a: made.mint_a,
+            mint_b: made.mint_b,
+            amount,
+            paid,
+            remaining,
+            timestamp: 200,
+        }
+    }
+
+    #[test]
+    fn tracks_fills_until_the_offer_is_filled() {
+        let mut store = OfferStore::open_in_memory().unwrap();
+        let made = made(Pubkey::new_unique(), 1_000, 2_000);
+
+        store
+            .apply("make", &[EscrowEvent::Made(made.clone())])
+            .unwrap();
+        store
+            .apply("take-1", &[EscrowEvent::Taken(taken(&made, 400, 800, 600))])
+            .unwrap();
+
+        let offer = store.offer(&made.escrow).unwrap().unwrap();
+        assert_eq!(offer.status, OfferStatus::Open);
+        assert_eq!((offer.filled, offer.received), (400, 800));
+        assert_eq!(offer.closed_at, None);
+
+        store
+            .apply("take-2", &[EscrowEvent::Taken(taken(&made, 600, 1_200, 0))])
+            .unwrap();
+
+        let offer = store.offer(&made.escrow).unwrap().unwrap();
+        assert_eq!(offer.status, OfferStatus::Filled);
+        assert_eq!((offer.filled, offer.received), (1_000, 2_000));
+        assert_eq!(offer.closed_at, Some(200));
+        assert_eq!(store.cursor().unwrap().as_deref(), Some("take-2"));
+    }
+
+    #[test]
+    fn refund_closes_only_the_open_offer_at_a_reused_address() {
+        let mut store = OfferStore::open_in_memory().unwrap();
+        let escrow = Pubkey::new_unique();
+        let first = made(escrow, 10, 10);
+        let second = made(escrow, 20, 20);
+
+        store
+            .apply("make-1", &[EscrowEvent::Made(first.clone())])
+            .unwrap();
+        store
+            .apply("take", &[EscrowEvent::Taken(taken(&first, 10, 10, 0))])
+            .unwrap();
+        store
+            .apply("make-2", &[EscrowEvent::Made(second.clone())])
+            .unwrap();
+        store
+            .apply(
+                "refund",
+                &[EscrowEvent::Refunded(OfferRefunded {
+                    escrow,
+                    seed: second.seed,
+                    maker: second.maker,
+                    mint_a: second.mint_a,
+                    mint_b: second.mint_b,
+                    amount: 20,
+                    timestamp: 300,
+                })],
+            )
+            .unwrap();
+
+        assert_eq!(store.offers(OfferStatus::Filled).unwrap().len(), 1);
+        assert!(store.offers(OfferStatus::Open).unwrap().is_empty());
+
+        let refunded = store.offers(OfferStatus::Refunded).unwrap();
+        assert_eq!(refunded.len(), 1);
+        assert_eq!(refunded[0].amount, 20);
+        assert_eq!(refunded[0].signature, "make-2");
+        assert_eq!(refunded[0].closed_at, Some(300));
+    }
+
+    #[test]
+    fn amend_restarts_the_fills_on_the_new_terms() {
+        let mut store = OfferStore::open_in_memory().unwrap();
+        let made = made(Pubkey::new_unique(), 1_000, 2_000);
+
+        store
+            .apply("make", &[EscrowEvent::Made(made.clone())])
+            .unwrap();
+        store
+            .apply("take-1", &[EscrowEvent::Taken(taken(&made, 400, 800, 600))])
+            .unwrap();
+        store
+            .apply(
+                "amend",
+                &[EscrowEvent::Amended(OfferAmended {
+                    escrow: made.escrow,
+                    seed: made.seed,
+                    maker: made.maker,
+                    amount: 600,
+                    receive: 600,
+                    revision: 1,
+                    timestamp: 150,
+                })],
+            )
+            .unwrap();
+        store
+            .apply("take-2", &[EscrowEvent::Taken(taken(&made, 600, 600, 0))])
+            .unwrap();
+
+        let offer = store.offer(&made.escrow).unwrap().unwrap();
+        assert_eq!(offer.status, OfferStatus::Filled);
+        assert_eq!((offer.amount, offer.receive), (600, 600));
+        assert_eq!((offer.filled, offer.received), (600, 600));
+    }
+
+    #[test]
+    fn refund_without_a_known_offer_records_a_closed_row() {
+        let mut store = OfferStore::open_in_memory().unwrap();
+        let unseen = made(Pubkey::new_unique(), 50, 70);
+
+        store
+            .apply(
+                "refund",
+                &[EscrowEvent::Refunded(OfferRefunded {
+                    escrow: unseen.escrow,
+                    seed: unseen.seed,
+                    maker: unseen.maker,
+                    mint_a: unseen.mint_a,
+                    mint_b: unseen.mint_b,
+                    amount: 50,
+                    timestamp: 400,
+                })],
+            )
+            .unwrap();
+
+        let offer = store.offer(&unseen.escrow).unwrap().unwrap();
+        assert_eq!(offer.status, OfferStatus::Refunded);
+        assert_eq!((offer.amount, offer.receive), (50, 0));
+        assert_eq!((offer.opened_at, offer.closed_at), (400, Some(400)));
+        assert_eq!(offer.signature, "refund");
+    }
+
+    fn bundle_made(seed: u64) -> BundleMade {
+        BundleMade {
+            bundle: Pubkey::new_unique(),
+            seed,
+            maker: Pubkey::new_unique(),
+            offered: vec![
+                Leg {
+                    mint: Pubkey::new_unique(),
+                    amount: 10,
+                },
+                Leg {
+                    mint: Pubkey::new_unique(),
+                    amount: 20,
+                },
+            ],
+            requested: vec![Leg {
+                mint: Pubkey::new_unique(),
+                amount: 30,
+            }],
+            timestamp: 500,
+        }
+    }
+
+    #[test]
+    fn bundles_keep_every_leg() {
+        let mut store = OfferStore::open_in_memory().unwrap();
+        let made = bundle_made(7);
+        let taker = Pubkey::new_unique();
+
+        store
+            .apply("make-bundle", &[EscrowEvent::BundleMade(made.clone())])
+            .unwrap();
+
+        let bundle = store.bundle(&made.bundle).unwrap().unwrap();
+        assert_eq!(bundle.status, OfferStatus::Open);
+        assert_eq!(bundle.offered, made.offered);
+        assert_eq!(bundle.requested, made.requested);
+
+        store
+            .apply(
+                "take-bundle",
+                &[EscrowEvent::BundleTaken(BundleTaken {
+                    bundle: made.bundle,
+                    seed: made.seed,
+                    maker: made.maker,
+                    taker,
+                    offered: made.offered.clone(),
+                    requested: made.requested.clone(),
+                    timestamp: 510,
+                })],
+            )
+            .unwrap();
+
+        let filled = store.bundles(OfferStatus::Filled).unwrap();
+        assert_eq!(filled.len(), 1);
+        assert_eq!(filled[0].taker, Some(taker));
+        assert_eq!(filled[0].closed_at, Some(510));
+        assert_eq!(filled[0].offered.len(), 2);
+        assert!(store.offers(OfferStatus::Open).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bundle_refund_without_a_known_make_records_the_offered_legs() {
+        let mut store = OfferStore::open_in_memory().unwrap();
+        let unseen = bundle_made(8);
+
+        store
+            .apply(
+                "refund-bundle",
+                &[EscrowEvent::BundleRefunded(BundleRefunded {
+                    bundle: unseen.bundle,
+                    seed: unseen.seed,
+                    maker: unseen.maker,
+                    offered: unseen.offered.clone(),
+                    timestamp: 520,
+                })],
+            )
+            .unwrap();
+
+        let bundle = store.bundle(&unseen.bundle).unwrap().unwrap();
+        assert_eq!(bundle.status, OfferStatus::Refunded);
+        assert_eq!(bundle.offered, unseen.offered);
+        assert!(bundle.requested.is_empty());
+        assert_eq!(bundle.signature, "refund-bundle");
+    }
+}
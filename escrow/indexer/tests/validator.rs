@@ -0,0 +1,37 @@
+//! Runs the indexer against a local validator that has been through the
+//! escrow's TypeScript suite:
+//!
+//! ```text
+//! anchor test --detach
+//! cargo test -p escrow-indexer -- --ignored
+//! ```
+//!
+//! `ESCROW_RPC_URL` overrides the default `http://127.0.0.1:8899`.
+
+use escrow_indexer::{sync, OfferStatus, OfferStore, RpcClient};
+
+#[test]
+#[ignore = "needs solana-test-validator with the escrow test suite applied"]
+fn indexes_the_test_suite_offers() {
+    let url = std::env::var("ESCROW_RPC_URL").unwrap_or("http://127.0.0.1:8899".to_string());
+    let rpc = RpcClient::new(url);
+    let mut store = OfferStore::open_in_memory().unwrap();
+
+    let indexed = sync(&rpc, &mut store, &escrow::ID).unwrap();
+    assert!(indexed > 0);
+
+    // "Make escrow" / "Take escrow" fill seed 1 in one go, and
+    // "Make and refund escrow" refunds seed 2.
+    let filled = store.offers(OfferStatus::Filled).unwrap();
+    assert!(filled.iter().any(|o| o.seed == 1 && o.filled == o.amount));
+
+    let refunded = store.offers(OfferStatus::Refunded).unwrap();
+    assert!(refunded.iter().any(|o| o.seed == 2 && o.filled == 0));
+
+    // "Make and take bundle escrow" settles seed 7 with two offered legs.
+    let bundles = store.bundles(OfferStatus::Filled).unwrap();
+    assert!(bundles.iter().any(|b| b.seed == 7 && b.offered.len() == 2));
+
+    // A second pass starts from the cursor and finds nothing new.
+    assert_eq!(sync(&rpc, &mut store, &escrow::ID).unwrap(), 0);
+}
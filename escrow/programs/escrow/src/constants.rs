@@ -1,4 +1,28 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Most distinct mints a maker can deposit into one bundle.
+#[constant]
+pub const MAX_OFFERED_LEGS: usize = 4;
+
+/// Most distinct mints a maker can ask for in return for one bundle.
+#[constant]
+pub const MAX_REQUESTED_LEGS: usize = 4;
+
+/// Stands in for the mint of an escrow leg paid in native lamports.
+#[constant]
+pub const NATIVE_SOL: Pubkey = system_program::ID;
+
+/// Upper bound for the protocol fee, in basis points.
+#[constant]
+pub const MAX_FEE_BPS: u16 = 500;
+
+/// Offers one order book page can hold.
+#[constant]
+pub const BOOK_PAGE_ENTRIES: usize = 16;
+
+/// Largest spread, either way, a maker can put on an oracle price.
+#[constant]
+pub const MAX_SPREAD_BPS: u16 = 5_000;
@@ -1,7 +1,57 @@
 use anchor_lang::prelude::*;
 
 #[error_code]
-pub enum ErrorCode {
-    #[msg("Custom error message")]
-    CustomError,
+pub enum EscrowError {
+    #[msg("Offer and requested amounts must be greater than zero")]
+    InvalidAmount,
+    #[msg("Fill amount must be greater than zero and at most the remaining offer")]
+    InvalidFillAmount,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("The offer has expired")]
+    OfferExpired,
+    #[msg("The offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("The offer is reserved for another taker")]
+    UnauthorizedTaker,
+    #[msg("A bundle side needs 1 to N legs with distinct mints and non-zero amounts")]
+    InvalidBundle,
+    #[msg("Remaining accounts do not match the bundle legs")]
+    LegAccountsMismatch,
+    #[msg("The escrow does not have a native SOL leg on this side")]
+    NotNativeLeg,
+    #[msg("Only the admin can change the escrow config")]
+    Unauthorized,
+    #[msg("Fee is above the maximum")]
+    FeeTooHigh,
+    #[msg("Treasury account does not match the config")]
+    InvalidTreasury,
+    #[msg("The offer was amended after the revision the taker accepted")]
+    RevisionMismatch,
+    #[msg("Collection bids can only be taken with take_collection_bid")]
+    CollectionBid,
+    #[msg("The NFT is not a verified member of the requested collection")]
+    CollectionMismatch,
+    #[msg("Oracle offers can only be taken with take_oracle_offer")]
+    OracleOffer,
+    #[msg("The offer is not priced off an oracle")]
+    NotOracleOffer,
+    #[msg("Spread or price bounds are out of range")]
+    InvalidPriceBand,
+    #[msg("Price account is not a supported oracle or does not match the offer")]
+    InvalidOracle,
+    #[msg("The oracle price is stale")]
+    StalePrice,
+    #[msg("The oracle price is outside the maker's bounds")]
+    PriceOutOfBounds,
+    #[msg("The price is above what the taker accepted")]
+    SlippageExceeded,
+    #[msg("The take owes a protocol fee but no treasury account was passed")]
+    MissingTreasury,
+    #[msg("The order book page is full")]
+    BookPageFull,
+    #[msg("The offer is not listed on this order book page")]
+    WrongBookPage,
+    #[msg("Arithmetic overflow")]
+    Overflow,
 }
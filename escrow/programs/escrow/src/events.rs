@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::Leg;
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferMade {
+    pub escrow: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// Amount of mint_a that reached the vault.
+    pub amount: u64,
+    /// Amount of mint_b asked for the whole offer. Collection bids ask for a
+    /// number of NFTs and oracle offers, priced at take time, ask for zero.
+    pub receive: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferTaken {
+    pub escrow: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// Amount of mint_a released by this take, protocol fee included.
+    pub amount: u64,
+    /// Amount of mint_b counted against the ask, protocol fee included.
+    pub paid: u64,
+    /// Amount of mint_a still on offer afterwards. Zero means filled.
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+/// The rest of an offer was repriced. Fills so far are settled, so the offer
+/// starts over from these terms with nothing filled.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferAmended {
+    pub escrow: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    /// Amount of mint_a now in the vault.
+    pub amount: u64,
+    /// Amount of mint_b now asked for it.
+    pub receive: u64,
+    pub revision: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferRefunded {
+    pub escrow: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// Amount of mint_a returned to the maker.
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// A bundle was opened. Bundles have several legs per side, so they get their
+/// own events instead of the single-mint offer ones.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleMade {
+    pub bundle: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    /// Amount of each offered mint that reached its vault.
+    pub offered: Vec<Leg>,
+    pub requested: Vec<Leg>,
+    pub timestamp: i64,
+}
+
+/// A bundle was settled. Bundles are always taken whole.
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleTaken {
+    pub bundle: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub offered: Vec<Leg>,
+    pub requested: Vec<Leg>,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleRefunded {
+    pub bundle: Pubkey,
+    pub seed: u64,
+    pub maker: Pubkey,
+    /// Offered legs as listed; the maker gets each vault's whole balance.
+    pub offered: Vec<Leg>,
+    pub timestamp: i64,
+}
@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{error::EscrowError, Escrow, OfferAmended, OrderBook};
+
+#[derive(Accounts)]
+pub struct Amend<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = maker,
+        constraint = escrow.collection.is_none() @ EscrowError::CollectionBid,
+        constraint = escrow.oracle.is_none() @ EscrowError::OracleOffer,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Amend<'info> {
+    /// Reprices what is left of the offer to `amount` of mint_a for `recieve`
+    /// of mint_b, topping the vault up from the maker or handing the excess
+    /// back. Fills so far are settled, so the new terms start from zero.
+    pub fn amend(&mut self, recieve: u64, amount: u64) -> Result<()> {
+        require!(recieve > 0 && amount > 0, EscrowError::InvalidAmount);
+
+        let current = self.vault.amount;
+
+        if amount > current {
+            let cpi_accounts = TransferChecked {
+                mint: self.mint_a.to_account_info(),
+                from: self.maker_ata_a.to_account_info(),
+                to: self.vault.to_account_info(),
+                authority: self.maker.to_account_info(),
+            };
+
+            let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+            transfer_checked(cpi_context, amount - current, self.mint_a.decimals)?;
+        } else if amount < current {
+            let signer_seeds: &[&[&[u8]]; 1] = &[&[
+                b"escrow",
+                self.maker.key.as_ref(),
+                &self.escrow.seed.to_le_bytes()[..],
+                &[self.escrow.bump],
+            ]];
+
+            let cpi_accounts = TransferChecked {
+                mint: self.mint_a.to_account_info(),
+                from: self.vault.to_account_info(),
+                to: self.maker_ata_a.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+
+            let cpi_context = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            );
+
+            transfer_checked(cpi_context, current - amount, self.mint_a.decimals)?;
+        }
+
+        self.vault.reload()?;
+
+        let escrow = &mut self.escrow;
+        escrow.amount = self.vault.amount;
+        escrow.recieve = recieve;
+        escrow.filled = 0;
+        escrow.received = 0;
+        escrow.revision += 1;
+
+        self.book
+            .update(self.escrow.key(), self.escrow.amount, self.escrow.recieve);
+
+        emit!(OfferAmended {
+            escrow: self.escrow.key(),
+            seed: self.escrow.seed,
+            maker: self.maker.key(),
+            amount: self.escrow.amount,
+            receive: self.escrow.recieve,
+            revision: self.escrow.revision,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
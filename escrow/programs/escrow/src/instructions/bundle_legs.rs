@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, Create},
+    token_interface::{close_account, transfer_checked, CloseAccount, Mint, TransferChecked},
+};
+
+use crate::{error::EscrowError, transfer_fee::harvest_withheld, Leg};
+
+/// The four remaining accounts that move one leg of a bundle, in the order
+/// `[mint, from, to, token_program]`.
+pub struct LegAccounts<'info> {
+    pub mint: &'info AccountInfo<'info>,
+    pub from: &'info AccountInfo<'info>,
+    pub to: &'info AccountInfo<'info>,
+    pub token_program: &'info AccountInfo<'info>,
+}
+
+/// Splits `accounts` into one group per leg and checks that every group
+/// carries the leg's mint and the token program that owns it.
+pub fn leg_accounts<'info>(
+    legs: &[Leg],
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<Vec<LegAccounts<'info>>> {
+    require_eq!(
+        accounts.len(),
+        legs.len() * 4,
+        EscrowError::LegAccountsMismatch
+    );
+
+    legs.iter()
+        .zip(accounts.chunks(4))
+        .map(|(leg, group)| {
+            let [mint, from, to, token_program] = group else {
+                return err!(EscrowError::LegAccountsMismatch);
+            };
+
+            require_keys_eq!(mint.key(), leg.mint, EscrowError::LegAccountsMismatch);
+            require_keys_eq!(
+                *mint.owner,
+                token_program.key(),
+                EscrowError::LegAccountsMismatch
+            );
+
+            Ok(LegAccounts {
+                mint,
+                from,
+                to,
+                token_program,
+            })
+        })
+        .collect()
+}
+
+impl<'info> LegAccounts<'info> {
+    /// Checks that `account` is the ATA of `authority` for this leg's mint.
+    pub fn require_ata(&self, account: &AccountInfo<'info>, authority: &Pubkey) -> Result<()> {
+        require_keys_eq!(
+            account.key(),
+            get_associated_token_address_with_program_id(
+                authority,
+                self.mint.key,
+                self.token_program.key
+            ),
+            EscrowError::LegAccountsMismatch
+        );
+
+        Ok(())
+    }
+
+    /// Creates the ATA of `authority` for this leg's mint unless it exists.
+    pub fn create_ata(
+        &self,
+        account: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        associated_token_program: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        self.require_ata(account, authority.key)?;
+
+        let cpi_accounts = Create {
+            payer: payer.clone(),
+            associated_token: account.clone(),
+            authority: authority.clone(),
+            mint: self.mint.clone(),
+            system_program: system_program.clone(),
+            token_program: self.token_program.clone(),
+        };
+
+        create_idempotent(CpiContext::new(
+            associated_token_program.clone(),
+            cpi_accounts,
+        ))
+    }
+
+    pub fn transfer(
+        &self,
+        authority: &AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        self.transfer_to(self.to, authority, amount, signer_seeds)
+    }
+
+    /// Same as `transfer`, but into `to` instead of the group's destination.
+    pub fn transfer_to(
+        &self,
+        to: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mint = InterfaceAccount::<Mint>::try_from(self.mint)?;
+
+        let cpi_accounts = TransferChecked {
+            from: self.from.clone(),
+            mint: self.mint.clone(),
+            to: to.clone(),
+            authority: authority.clone(),
+        };
+
+        let cpi_ctx =
+            CpiContext::new_with_signer(self.token_program.clone(), cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, mint.decimals)
+    }
+
+    /// Closes the `from` account, which must be empty by now, into
+    /// `destination`. Transfer fees withheld on it are harvested into the
+    /// mint first, so the mint must be passed writable.
+    pub fn close_from(
+        &self,
+        destination: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        harvest_withheld(self.token_program, self.mint, self.from)?;
+
+        let close_accounts = CloseAccount {
+            account: self.from.clone(),
+            destination: destination.clone(),
+            authority: authority.clone(),
+        };
+
+        let close_ctx =
+            CpiContext::new_with_signer(self.token_program.clone(), close_accounts, signer_seeds);
+
+        close_account(close_ctx)
+    }
+}
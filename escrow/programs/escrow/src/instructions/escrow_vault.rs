@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+use crate::{
+    protocol_fee::treasury,
+    transfer_fee::{gross_up, harvest_withheld},
+    Escrow,
+};
+
+/// Pays `amount` of mint_b from the taker, grossed up for the transfer fee
+/// when the offer asks for it, so `to` receives `amount` net.
+pub fn pay_mint_b<'info>(
+    escrow: &Account<'info, Escrow>,
+    mint_b: &InterfaceAccount<'info, Mint>,
+    taker_ata_b: &InterfaceAccount<'info, TokenAccount>,
+    taker: &Signer<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let amount = if escrow.gross_up {
+        gross_up(&mint_b.to_account_info(), amount)?
+    } else {
+        amount
+    };
+
+    let cpi_accounts = TransferChecked {
+        mint: mint_b.to_account_info(),
+        from: taker_ata_b.to_account_info(),
+        to,
+        authority: taker.to_account_info(),
+    };
+
+    let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+
+    transfer_checked(cpi_context, amount, mint_b.decimals)
+}
+
+/// Releases `amount` of mint_a to `to`, less `fee`, which goes to the
+/// treasury. Take accounts leave the escrow open for partial fills, so the
+/// last fill also takes anything else left in the vault and closes the vault
+/// and the escrow to the maker.
+#[allow(clippy::too_many_arguments)]
+pub fn release_fill<'info>(
+    escrow: &mut Account<'info, Escrow>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    maker: &SystemAccount<'info>,
+    to: AccountInfo<'info>,
+    treasury_ata: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+    fee: u64,
+) -> Result<()> {
+    let seed = escrow.seed.to_le_bytes();
+    let bump = [escrow.bump];
+    let signer_seeds: &[&[&[u8]]; 1] = &[&[b"escrow", maker.key.as_ref(), &seed, &bump]];
+
+    let release = escrow.release_for(amount, vault.amount);
+
+    release_mint_a(
+        escrow,
+        mint_a,
+        vault,
+        token_program,
+        to,
+        release - fee,
+        signer_seeds,
+    )?;
+
+    if fee > 0 {
+        let treasury = treasury(treasury_ata)?;
+
+        release_mint_a(
+            escrow,
+            mint_a,
+            vault,
+            token_program,
+            treasury,
+            fee,
+            signer_seeds,
+        )?;
+    }
+
+    escrow.filled += amount;
+
+    if !escrow.is_filled() {
+        return Ok(());
+    }
+
+    let close_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: maker.to_account_info(),
+        authority: escrow.to_account_info(),
+    };
+
+    let close_cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        close_accounts,
+        signer_seeds,
+    );
+
+    harvest_withheld(
+        &token_program.to_account_info(),
+        &mint_a.to_account_info(),
+        &vault.to_account_info(),
+    )?;
+
+    close_account(close_cpi_ctx)?;
+
+    escrow.close(maker.to_account_info())
+}
+
+fn release_mint_a<'info>(
+    escrow: &Account<'info, Escrow>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    to: AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        mint: mint_a.to_account_info(),
+        to,
+        authority: escrow.to_account_info(),
+    };
+
+    let cpi_context =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+
+    transfer_checked(cpi_context, amount, mint_a.decimals)
+}
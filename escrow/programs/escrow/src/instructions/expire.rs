@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{
+    error::EscrowError, transfer_fee::harvest_withheld, unlist_offer, Escrow, OrderBook,
+};
+
+/// Permissionless cleanup of an expired offer. Anyone can send it; the
+/// tokens and rent go back to the maker and the caller collects the tip.
+#[derive(Accounts)]
+pub struct Expire<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    // Created on the caller's dime if the maker has closed it in the meantime,
+    // so the maker cannot keep a stale offer alive.
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Page the offer is listed on, if the caller passes it; see `unlist_offer`.
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Option<Box<Account<'info, OrderBook>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Expire<'info> {
+
+    pub fn return_and_close_vault(&mut self) -> Result<()> {
+        require!(
+            self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::OfferNotExpired
+        );
+
+        unlist_offer(self.book.as_deref_mut(), &self.escrow.key());
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let decimals = self.mint_a.decimals;
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: self.vault.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_context, self.vault.amount, decimals)?;
+
+        // Indexers see an expired offer the same way as a refunded one.
+        emit!(self
+            .escrow
+            .refunded_event(self.escrow.key(), self.vault.amount)?);
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        harvest_withheld(
+            &self.token_program.to_account_info(),
+            &self.mint_a.to_account_info(),
+            &self.vault.to_account_info(),
+        )?;
+
+        close_account(close_cpi_ctx)?;
+
+        // The rest of the escrow lamports reach the maker through `close`.
+        let tip = self.escrow.tip;
+        self.escrow.sub_lamports(tip)?;
+        self.caller.add_lamports(tip)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::OrderBook;
+
+/// Opens page `page` of the order book of a mint pair. Anyone can pay for
+/// it; makers list their offers on any page with room left.
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct InitBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Only used as a seed. Either a mint or `NATIVE_SOL`.
+    pub mint_a: UncheckedAccount<'info>,
+
+    /// CHECK: Only used as a seed. Either a mint or `NATIVE_SOL`.
+    pub mint_b: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref(), page.to_le_bytes().as_ref()],
+        bump,
+        space = OrderBook::space(0),
+    )]
+    pub book: Account<'info, OrderBook>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitBook<'info> {
+
+    pub fn init_book(&mut self, page: u32, bumps: &InitBookBumps) -> Result<()> {
+        self.book.set_inner(OrderBook {
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            page,
+            bump: bumps.book,
+            entries: Vec::new(),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::EscrowError, program::Escrow as EscrowProgram, Config, FeeSide, MAX_FEE_BPS};
+
+/// Creates the config once. Only the program's upgrade authority may do it,
+/// so nobody can claim the admin role before the deployer.
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"config"],
+        bump,
+        space = 8 + Config::INIT_SPACE,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, EscrowProgram>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitConfig<'info> {
+
+    pub fn init_config(
+        &mut self,
+        bumps: &InitConfigBumps,
+        fee_bps: u16,
+        fee_side: FeeSide,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::FeeTooHigh);
+
+        self.config.set_inner(Config {
+            admin: self.admin.key(),
+            treasury,
+            fee_bps,
+            fee_side,
+            bump: bumps.config,
+        });
+
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
 use anchor_spl::{
     associated_token::AssociatedToken, 
     token_interface::{ Mint, TokenAccount, TransferChecked, TokenInterface, transfer_checked}
 };
 
-use crate::Escrow;
+use crate::{error::EscrowError, list_offer, Escrow, OrderBook};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -51,6 +54,13 @@ pub struct Make<'info> {
         associated_token::token_program = token_program    
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
     
     pub token_program: Interface<'info, TokenInterface>,
     
@@ -61,20 +71,45 @@ pub struct Make<'info> {
 
 impl<'info> Make<'info> {
 
-    pub fn init_escrow(&mut self, seed: u64, bump: &MakeBumps, recieve: u64) -> Result<()>{
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        bump: &MakeBumps,
+        recieve: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+        gross_up: bool,
+    ) -> Result<()>{
+        Escrow::check_terms(recieve, expiry)?;
+
         self.escrow.set_inner(Escrow { 
             seed, 
             maker: self.maker.key(), 
             mint_a: self.mint_a.key(), 
             mint_b: self.mint_b.key(), 
             recieve, 
-            bump: bump.escrow 
+            bump: bump.escrow,
+            amount: 0,
+            filled: 0,
+            received: 0,
+            expiry,
+            tip,
+            taker,
+            gross_up,
+            revision: 0,
+            collection: None,
+            oracle: None,
+            book_page: Some(self.book.page),
         });
 
         Ok(())
     } 
 
     pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+
         let decimals = self.mint_a.decimals;
         let cpi_program = self.token_program.to_account_info();
 
@@ -87,6 +122,43 @@ impl<'info> Make<'info> {
 
         let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_context, amount, decimals)
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Token-2022 transfer fees can leave the vault short of `amount`, and
+        // the offer is priced on what actually arrived.
+        self.vault.reload()?;
+        require!(self.vault.amount > 0, EscrowError::InvalidAmount);
+        self.escrow.amount = self.vault.amount;
+
+        emit!(self.escrow.made_event(self.escrow.key())?);
+
+        Ok(())
+    }
+
+    /// Moves the cleanup tip onto the escrow account. It goes back to the
+    /// maker with the rent unless the offer is cleaned up by `expire`.
+    pub fn deposit_tip(&mut self) -> Result<()> {
+        if self.escrow.tip == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from: self.maker.to_account_info(),
+            to: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+
+        transfer(cpi_context, self.escrow.tip)
+    }
+
+    /// Lists the new offer in the order book of its mint pair.
+    pub fn list_offer(&mut self) -> Result<()> {
+        list_offer(
+            &mut self.book,
+            &self.escrow,
+            &self.maker.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
     }
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenAccount};
+
+use crate::{
+    bundle_legs::leg_accounts, error::EscrowError, BundleEscrow, Leg, MAX_OFFERED_LEGS,
+    MAX_REQUESTED_LEGS,
+};
+
+/// Remaining accounts: one `[mint, maker_ata, vault, token_program]` group per
+/// offered leg, in the order of `offered`. Each vault is the bundle's ATA for
+/// that mint and is created here.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeBundle<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"bundle", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + BundleEscrow::INIT_SPACE,
+    )]
+    pub bundle: Account<'info, BundleEscrow>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeBundle<'info> {
+
+    pub fn make_bundle(
+        &mut self,
+        seed: u64,
+        mut offered: Vec<Leg>,
+        requested: Vec<Leg>,
+        gross_up: bool,
+        bumps: &MakeBundleBumps,
+        accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        BundleEscrow::check_legs(&offered, MAX_OFFERED_LEGS)?;
+        BundleEscrow::check_legs(&requested, MAX_REQUESTED_LEGS)?;
+
+        let legs = leg_accounts(&offered, accounts)?;
+
+        for (leg, accounts) in offered.iter_mut().zip(&legs) {
+            accounts.create_ata(
+                accounts.to,
+                &self.bundle.to_account_info(),
+                &self.maker.to_account_info(),
+                &self.associated_token_program.to_account_info(),
+                &self.system_program.to_account_info(),
+            )?;
+
+            accounts.transfer(&self.maker.to_account_info(), leg.amount, &[])?;
+
+            // Record what reached the vault after any Token-2022 transfer fee.
+            leg.amount = InterfaceAccount::<TokenAccount>::try_from(accounts.to)?.amount;
+            require!(leg.amount > 0, EscrowError::InvalidBundle);
+        }
+
+        self.bundle.set_inner(BundleEscrow {
+            seed,
+            maker: self.maker.key(),
+            offered,
+            requested,
+            gross_up,
+            bump: bumps.bundle,
+        });
+
+        emit!(self.bundle.made_event(self.bundle.key())?);
+
+        Ok(())
+    }
+}
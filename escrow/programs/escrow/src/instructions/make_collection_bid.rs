@@ -0,0 +1,153 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::EscrowError, list_offer, Escrow, OrderBook};
+
+/// Bids mint_a for NFTs of a Metaplex collection. The escrow is stored with
+/// the collection mint as `mint_b`, so `refund`, `expire` and `amend` work on
+/// it unchanged and it is listed in the `(mint_a, collection)` book.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeCollectionBid<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    pub collection_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Escrow::INIT_SPACE,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"book", mint_a.key().as_ref(), collection_mint.key().as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeCollectionBid<'info> {
+
+    /// Opens a bid of `amount` mint_a for `count` NFTs of the collection.
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        bump: &MakeCollectionBidBumps,
+        count: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        Escrow::check_terms(count, expiry)?;
+
+        self.escrow.set_inner(Escrow {
+            seed,
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.collection_mint.key(),
+            recieve: count,
+            bump: bump.escrow,
+            amount: 0,
+            filled: 0,
+            received: 0,
+            expiry,
+            tip,
+            taker,
+            gross_up: false,
+            revision: 0,
+            collection: Some(self.collection_mint.key()),
+            oracle: None,
+            book_page: Some(self.book.page),
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+
+        let cpi_accounts = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: self.maker_ata_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        transfer_checked(cpi_context, amount, self.mint_a.decimals)?;
+
+        // Every NFT of the bid must be paid at least one base unit, or
+        // `fill_for` would round a fill down to nothing.
+        self.vault.reload()?;
+        require!(
+            self.vault.amount >= self.escrow.recieve,
+            EscrowError::InvalidAmount
+        );
+        self.escrow.amount = self.vault.amount;
+
+        emit!(self.escrow.made_event(self.escrow.key())?);
+
+        if self.escrow.tip == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from: self.maker.to_account_info(),
+            to: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+
+        transfer(cpi_context, self.escrow.tip)
+    }
+
+    /// Lists the new bid in the order book of its mint and collection.
+    pub fn list_offer(&mut self) -> Result<()> {
+        list_offer(
+            &mut self.book,
+            &self.escrow,
+            &self.maker.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
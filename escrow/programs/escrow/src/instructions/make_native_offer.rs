@@ -0,0 +1,127 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::token_interface::{Mint, TokenInterface};
+
+use crate::{error::EscrowError, list_offer, Escrow, OrderBook, NATIVE_SOL};
+
+/// Offers lamports for mint_b. The lamports wait in a system-owned PDA
+/// instead of being wrapped into a token vault.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeNativeOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Escrow::INIT_SPACE,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", escrow.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"book", NATIVE_SOL.as_ref(), mint_b.key().as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeNativeOffer<'info> {
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        bump: &MakeNativeOfferBumps,
+        lamports: u64,
+        recieve: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+        gross_up: bool,
+    ) -> Result<()> {
+        require!(lamports > 0, EscrowError::InvalidAmount);
+        Escrow::check_terms(recieve, expiry)?;
+
+        self.escrow.set_inner(Escrow {
+            seed,
+            maker: self.maker.key(),
+            mint_a: NATIVE_SOL,
+            mint_b: self.mint_b.key(),
+            recieve,
+            bump: bump.escrow,
+            amount: lamports,
+            filled: 0,
+            received: 0,
+            expiry,
+            tip,
+            taker,
+            gross_up,
+            revision: 0,
+            collection: None,
+            oracle: None,
+            book_page: Some(self.book.page),
+        });
+
+        Ok(())
+    }
+
+    /// Funds the SOL vault with the offer plus its rent-exempt minimum, so
+    /// partial fills never leave it below rent. The minimum goes back to the
+    /// maker when the offer closes.
+    pub fn deposit(&mut self) -> Result<()> {
+        let rent = Rent::get()?.minimum_balance(0);
+
+        self.transfer_from_maker(self.sol_vault.to_account_info(), self.escrow.amount + rent)?;
+
+        if self.escrow.tip > 0 {
+            self.transfer_from_maker(self.escrow.to_account_info(), self.escrow.tip)?;
+        }
+
+        emit!(self.escrow.made_event(self.escrow.key())?);
+
+        Ok(())
+    }
+
+    fn transfer_from_maker(&self, to: AccountInfo<'info>, lamports: u64) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from: self.maker.to_account_info(),
+            to,
+        };
+
+        let cpi_context = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+
+        transfer(cpi_context, lamports)
+    }
+
+    /// Lists the new offer in the order book of its mint pair.
+    pub fn list_offer(&mut self) -> Result<()> {
+        list_offer(
+            &mut self.book,
+            &self.escrow,
+            &self.maker.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
\ No newline at end of file
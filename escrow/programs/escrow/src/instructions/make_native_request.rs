@@ -0,0 +1,146 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::EscrowError, list_offer, Escrow, OrderBook, NATIVE_SOL};
+
+/// Offers mint_a for lamports. `refund` and `expire` work on these escrows
+/// unchanged, since the offered side is an ordinary token vault.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeNativeRequest<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Escrow::INIT_SPACE,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"book", mint_a.key().as_ref(), NATIVE_SOL.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeNativeRequest<'info> {
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        bump: &MakeNativeRequestBumps,
+        amount: u64,
+        lamports: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+        Escrow::check_terms(lamports, expiry)?;
+
+        self.escrow.set_inner(Escrow {
+            seed,
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: NATIVE_SOL,
+            recieve: lamports,
+            bump: bump.escrow,
+            amount,
+            filled: 0,
+            received: 0,
+            expiry,
+            tip,
+            taker,
+            gross_up: false,
+            revision: 0,
+            collection: None,
+            oracle: None,
+            book_page: Some(self.book.page),
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: self.maker_ata_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        transfer_checked(cpi_context, self.escrow.amount, self.mint_a.decimals)?;
+
+        // Token-2022 transfer fees can leave the vault short of the nominal amount.
+        self.vault.reload()?;
+        require!(self.vault.amount > 0, EscrowError::InvalidAmount);
+        self.escrow.amount = self.vault.amount;
+
+        emit!(self.escrow.made_event(self.escrow.key())?);
+
+        if self.escrow.tip == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from: self.maker.to_account_info(),
+            to: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+
+        transfer(cpi_context, self.escrow.tip)
+    }
+
+    /// Lists the new offer in the order book of its mint pair.
+    pub fn list_offer(&mut self) -> Result<()> {
+        list_offer(
+            &mut self.book,
+            &self.escrow,
+            &self.maker.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
\ No newline at end of file
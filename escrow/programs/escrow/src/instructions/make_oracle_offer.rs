@@ -0,0 +1,141 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::EscrowError, oracle_price::check_oracle, Escrow, OraclePricing};
+
+/// Offers mint_a at a spread over an oracle price instead of a fixed ask.
+/// With no fixed price the offer is not listed in the pair's order book.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeOracleOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Checked against the pricing source in `init_escrow`.
+    pub price_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Escrow::INIT_SPACE,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeOracleOffer<'info> {
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        bump: &MakeOracleOfferBumps,
+        pricing: OraclePricing,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+        gross_up: bool,
+    ) -> Result<()> {
+        Escrow::check_expiry(expiry)?;
+        pricing.check()?;
+
+        require_keys_eq!(self.price_account.key(), pricing.feed, EscrowError::InvalidOracle);
+        check_oracle(pricing.source, &self.price_account)?;
+
+        self.escrow.set_inner(Escrow {
+            seed,
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            recieve: 0,
+            bump: bump.escrow,
+            amount: 0,
+            filled: 0,
+            received: 0,
+            expiry,
+            tip,
+            taker,
+            gross_up,
+            revision: 0,
+            collection: None,
+            oracle: Some(pricing),
+            book_page: None,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+
+        let cpi_accounts = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: self.maker_ata_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        transfer_checked(cpi_context, amount, self.mint_a.decimals)?;
+
+        self.vault.reload()?;
+        require!(self.vault.amount > 0, EscrowError::InvalidAmount);
+        self.escrow.amount = self.vault.amount;
+
+        emit!(self.escrow.made_event(self.escrow.key())?);
+
+        if self.escrow.tip == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from: self.maker.to_account_info(),
+            to: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+
+        transfer(cpi_context, self.escrow.tip)
+    }
+}
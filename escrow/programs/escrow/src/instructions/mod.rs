@@ -1,7 +1,51 @@
+pub mod amend;
+pub mod bundle_legs;
+pub mod escrow_vault;
+pub mod expire;
+pub mod expire_native_offer;
+pub mod init_book;
+pub mod init_config;
 pub mod make;
+pub mod make_bundle;
+pub mod make_collection_bid;
+pub mod make_native_offer;
+pub mod make_native_request;
+pub mod make_oracle_offer;
+pub mod oracle_price;
+pub mod protocol_fee;
+pub mod prune_book;
 pub mod refund;
+pub mod refund_bundle;
+pub mod refund_native_offer;
+pub mod sol_vault;
 pub mod take;
+pub mod take_bundle;
+pub mod take_collection_bid;
+pub mod take_native_offer;
+pub mod take_native_request;
+pub mod take_oracle_offer;
+pub mod transfer_fee;
+pub mod update_config;
 
+pub use amend::*;
+pub use expire::*;
+pub use expire_native_offer::*;
+pub use init_book::*;
+pub use init_config::*;
 pub use make::*;
+pub use make_bundle::*;
+pub use make_collection_bid::*;
+pub use make_native_offer::*;
+pub use make_native_request::*;
+pub use make_oracle_offer::*;
+pub use prune_book::*;
 pub use refund::*;
-pub use take::*;
\ No newline at end of file
+pub use refund_bundle::*;
+pub use refund_native_offer::*;
+pub use take::*;
+pub use take_bundle::*;
+pub use take_collection_bid::*;
+pub use take_native_offer::*;
+pub use take_native_request::*;
+pub use take_oracle_offer::*;
+pub use update_config::*;
\ No newline at end of file
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use switchboard_on_demand::{sb_pid, PullFeedAccountData};
+
+use crate::{error::EscrowError, OraclePrice, OracleSource};
+
+/// Scales above this are cut down before pricing, which keeps the math in
+/// `u128` for any realistic price.
+const MAX_PRICE_SCALE: u32 = 12;
+
+/// Checks that `account` is an oracle of `source` without reading a price.
+pub fn check_oracle(source: OracleSource, account: &AccountInfo) -> Result<()> {
+    match source {
+        OracleSource::Switchboard => {
+            require!(
+                account.owner.to_bytes() == sb_pid().to_bytes(),
+                EscrowError::InvalidOracle
+            );
+            PullFeedAccountData::parse(account.data.borrow())
+                .map(|_| ())
+                .map_err(|_| error!(EscrowError::InvalidOracle))
+        }
+    }
+}
+
+/// Reads the current price of `account`. Switchboard results older than
+/// `max_staleness` slots are rejected.
+pub fn read_price(
+    source: OracleSource,
+    account: &AccountInfo,
+    max_staleness: u64,
+) -> Result<OraclePrice> {
+    let price = match source {
+        OracleSource::Switchboard => {
+            require!(
+                account.owner.to_bytes() == sb_pid().to_bytes(),
+                EscrowError::InvalidOracle
+            );
+
+            let feed = PullFeedAccountData::parse(account.data.borrow())
+                .map_err(|_| error!(EscrowError::InvalidOracle))?;
+            let value = feed
+                .get_value(&Clock::get()?, max_staleness, 1, true)
+                .map_err(|_| error!(EscrowError::StalePrice))?;
+
+            OraclePrice {
+                mantissa: u128::try_from(value.mantissa())
+                    .map_err(|_| error!(EscrowError::InvalidOracle))?,
+                scale: value.scale(),
+            }
+        }
+    };
+
+    require!(price.mantissa > 0, EscrowError::InvalidOracle);
+
+    if price.scale <= MAX_PRICE_SCALE {
+        return Ok(price);
+    }
+
+    Ok(OraclePrice {
+        mantissa: price.mantissa / 10u128.pow(price.scale - MAX_PRICE_SCALE),
+        scale: MAX_PRICE_SCALE,
+    })
+}
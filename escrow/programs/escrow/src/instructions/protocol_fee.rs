@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+use crate::error::EscrowError;
+
+/// The treasury account a take pays its protocol fee into. Takes only have
+/// to pass one when the fee they owe is non-zero, so call this only then.
+pub fn treasury<'info, T: ToAccountInfo<'info>>(treasury: Option<&T>) -> Result<AccountInfo<'info>> {
+    treasury
+        .map(ToAccountInfo::to_account_info)
+        .ok_or_else(|| error!(EscrowError::MissingTreasury))
+}
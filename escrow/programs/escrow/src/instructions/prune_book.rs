@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{Escrow, OrderBook};
+
+/// Drops entries of offers that were refunded or expired without the book,
+/// freeing their slots. Anyone can send it with the listed escrows as
+/// remaining accounts; entries of live offers on this page of this pair are
+/// left alone.
+#[derive(Accounts)]
+pub struct PruneBook<'info> {
+    #[account(
+        mut,
+        seeds = [b"book", book.mint_a.as_ref(), book.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+    )]
+    pub book: Account<'info, OrderBook>,
+}
+
+impl<'info> PruneBook<'info> {
+
+    pub fn prune(&mut self, escrows: &'info [AccountInfo<'info>]) -> Result<()> {
+        for info in escrows {
+            let listed_here = Account::<Escrow>::try_from(info).is_ok_and(|escrow| {
+                escrow.mint_a == self.book.mint_a
+                    && escrow.mint_b == self.book.mint_b
+                    && escrow.book_page == Some(self.book.page)
+            });
+
+            if !listed_here {
+                self.book.remove(info.key);
+            }
+        }
+
+        Ok(())
+    }
+}
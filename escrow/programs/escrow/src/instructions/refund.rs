@@ -7,7 +7,9 @@ use anchor_spl::{
     },
 };
 
-use crate::Escrow;
+use crate::{
+    error::EscrowError, transfer_fee::harvest_withheld, unlist_offer, Escrow, OrderBook,
+};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
@@ -16,6 +18,7 @@ pub struct Refund<'info> {
     pub maker: Signer<'info>,
 
     #[account(
+        mut,
         mint::token_program = token_program
     )]
     pub mint_a: InterfaceAccount<'info, Mint>,
@@ -45,6 +48,15 @@ pub struct Refund<'info> {
         associated_token::token_program = token_program    
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Page the offer is listed on, if the caller passes it; see `unlist_offer`.
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Option<Box<Account<'info, OrderBook>>>,
     
     pub token_program: Interface<'info, TokenInterface>,
     
@@ -56,6 +68,7 @@ pub struct Refund<'info> {
 impl<'info> Refund<'info> {
  
     pub fn refund_and_close_vault(&mut self) -> Result<()> {
+        unlist_offer(self.book.as_deref_mut(), &self.escrow.key());
 
         let signer_seeds: &[&[&[u8]]; 1] = &[&[
             b"escrow", 
@@ -78,6 +91,10 @@ impl<'info> Refund<'info> {
 
         transfer_checked(cpi_context, self.vault.amount, decimals)?;
 
+        emit!(self
+            .escrow
+            .refunded_event(self.escrow.key(), self.vault.amount)?);
+
         let close_accounts = CloseAccount {
             account: self.vault.to_account_info(),
             destination: self.maker.to_account_info(),
@@ -90,6 +107,12 @@ impl<'info> Refund<'info> {
             signer_seeds
         );
 
+        harvest_withheld(
+            &self.token_program.to_account_info(),
+            &self.mint_a.to_account_info(),
+            &self.vault.to_account_info(),
+        )?;
+
         close_account(close_cpi_ctx)?;
 
         Ok(())
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{bundle_legs::leg_accounts, BundleEscrow};
+
+/// Remaining accounts: one `[mint, vault, maker_ata, token_program]` group per
+/// offered leg, in the order stored on the bundle.
+#[derive(Accounts)]
+pub struct RefundBundle<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"bundle", maker.key().as_ref(), bundle.seed.to_le_bytes().as_ref()],
+        bump = bundle.bump,
+    )]
+    pub bundle: Account<'info, BundleEscrow>,
+}
+
+impl<'info> RefundBundle<'info> {
+
+    pub fn refund_bundle(&mut self, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let offered = leg_accounts(&self.bundle.offered, accounts)?;
+
+        let bundle = self.bundle.to_account_info();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"bundle",
+            self.maker.key.as_ref(),
+            &self.bundle.seed.to_le_bytes()[..],
+            &[self.bundle.bump],
+        ]];
+
+        for accounts in &offered {
+            accounts.require_ata(accounts.from, bundle.key)?;
+
+            let vault = InterfaceAccount::<TokenAccount>::try_from(accounts.from)?;
+
+            accounts.transfer(&bundle, vault.amount, signer_seeds)?;
+            accounts.close_from(&self.maker.to_account_info(), &bundle, signer_seeds)?;
+        }
+
+        emit!(self.bundle.refunded_event(self.bundle.key())?);
+
+        Ok(())
+    }
+}
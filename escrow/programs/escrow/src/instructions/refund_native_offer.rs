@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::EscrowError, sol_vault::pay_from_sol_vault, unlist_offer, Escrow, OrderBook,
+};
+
+#[derive(Accounts)]
+pub struct RefundNativeOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        constraint = escrow.is_native_offer() @ EscrowError::NotNativeLeg,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", escrow.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Page the offer is listed on, if the caller passes it; see `unlist_offer`.
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Option<Box<Account<'info, OrderBook>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundNativeOffer<'info> {
+
+    pub fn refund_and_close_vault(&mut self, sol_vault_bump: u8) -> Result<()> {
+        unlist_offer(self.book.as_deref_mut(), &self.escrow.key());
+
+        emit!(self
+            .escrow
+            .refunded_event(self.escrow.key(), self.escrow.remaining())?);
+
+        pay_from_sol_vault(
+            &self.escrow,
+            &self.sol_vault,
+            sol_vault_bump,
+            &self.maker.to_account_info(),
+            &self.system_program,
+            self.sol_vault.lamports(),
+        )
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::Escrow;
+
+/// Moves `amount` lamports out of the `[b"sol_vault", escrow]` PDA that holds
+/// a native SOL offer.
+pub fn pay_from_sol_vault<'info>(
+    escrow: &Account<'info, Escrow>,
+    sol_vault: &SystemAccount<'info>,
+    sol_vault_bump: u8,
+    to: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    let escrow_key = escrow.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"sol_vault",
+        escrow_key.as_ref(),
+        &[sol_vault_bump],
+    ]];
+
+    let cpi_accounts = Transfer {
+        from: sol_vault.to_account_info(),
+        to: to.clone(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+
+    transfer(cpi_ctx, amount)
+}
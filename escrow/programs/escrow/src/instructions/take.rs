@@ -1,13 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
-        TransferChecked,
-    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::Escrow;
+use crate::{
+    error::EscrowError,
+    escrow_vault::{pay_mint_b, release_fill},
+    protocol_fee::treasury,
+    Config, Escrow, FeeSide, OrderBook,
+};
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -18,6 +20,7 @@ pub struct Take<'info> {
     pub maker: SystemAccount<'info>,
 
     #[account(
+        mut,
         mint::token_program = token_program
     )]
     pub mint_a: InterfaceAccount<'info, Mint>,
@@ -55,10 +58,11 @@ pub struct Take<'info> {
 
     #[account(
         mut,
-        close = maker,
         has_one = mint_a,
         has_one = maker,
         has_one = mint_b,
+        constraint = escrow.collection.is_none() @ EscrowError::CollectionBid,
+        constraint = escrow.oracle.is_none() @ EscrowError::OracleOffer,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
     )]
@@ -72,6 +76,29 @@ pub struct Take<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Treasury token account of the mint the fee is cut from, only needed
+    /// when the take owes a fee.
+    #[account(
+        mut,
+        constraint = treasury_ata.owner == config.treasury @ EscrowError::InvalidTreasury,
+        constraint = treasury_ata.mint == config.fee_mint(&escrow) @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -80,60 +107,63 @@ pub struct Take<'info> {
 
 impl<'info> Take<'info> {
 
-    pub fn deposit(&mut self) -> Result<()> {
-        let decimals = self.mint_b.decimals;
-        let cpi_program = self.token_program.to_account_info();
+    pub fn deposit(&mut self, amount: u64, expected_revision: Option<u64>) -> Result<()> {
+        self.escrow.check_revision(expected_revision)?;
+        self.escrow.check_fill(self.taker.key, amount)?;
+
+        let price = self.escrow.price_for(amount)?;
+        let (maker_amount, fee) = self.config.split_fee(FeeSide::MintB, price);
+
+        self.pay_mint_b(self.maker_ata_b.to_account_info(), maker_amount)?;
 
-        let cpi_accounts = TransferChecked {
-            mint: self.mint_b.to_account_info(),
-            from: self.taker_ata_b.to_account_info(),
-            to: self.maker_ata_b.to_account_info(),
-            authority: self.taker.to_account_info(),
-        };
+        if fee > 0 {
+            self.pay_mint_b(treasury(self.treasury_ata.as_deref())?, fee)?;
+        }
 
-        let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+        self.escrow.received += price;
 
-        transfer_checked(cpi_context, self.escrow.recieve, decimals)?;
+        emit!(self
+            .escrow
+            .taken_event(self.escrow.key(), self.taker.key(), amount, price)?);
 
         Ok(())
     }
 
-    pub fn transfer_and_close_vault(&mut self) -> Result<()> {
-        let signer_seeds: &[&[&[u8]]; 1] = &[&[
-            b"escrow",
-            self.maker.key.as_ref(),
-            &self.escrow.seed.to_le_bytes()[..],
-            &[self.escrow.bump],
-        ]];
-
-        let decimals = self.mint_a.decimals;
-        let cpi_program = self.token_program.to_account_info();
-
-        let cpi_accounts = TransferChecked {
-            from: self.vault.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-            to: self.taker_ata_a.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-
-        transfer_checked(cpi_context, self.vault.amount, decimals)?;
-
-        let close_accounts = CloseAccount {
-            account: self.vault.to_account_info(),
-            destination: self.maker.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let close_cpi_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            close_accounts,
-            signer_seeds,
-        );
+    fn pay_mint_b(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        pay_mint_b(
+            &self.escrow,
+            &self.mint_b,
+            &self.taker_ata_b,
+            &self.taker,
+            &self.token_program,
+            to,
+            amount,
+        )
+    }
 
-        close_account(close_cpi_ctx)?;
+    /// Releases `amount` of mint_a to the taker and updates the offer's entry
+    /// in the book.
+    pub fn transfer_and_close_vault(&mut self, amount: u64) -> Result<()> {
+        let (_, fee) = self.config.split_fee(FeeSide::MintA, amount);
+
+        release_fill(
+            &mut self.escrow,
+            &self.mint_a,
+            &self.vault,
+            &self.maker,
+            self.taker_ata_a.to_account_info(),
+            self.treasury_ata.as_deref(),
+            &self.token_program,
+            amount,
+            fee,
+        )?;
+
+        self.book.update(
+            self.escrow.key(),
+            self.escrow.remaining(),
+            self.escrow.remaining_ask(),
+        );
 
         Ok(())
     }
-}
\ No newline at end of file
+}
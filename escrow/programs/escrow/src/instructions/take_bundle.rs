@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenAccount};
+
+use crate::{
+    bundle_legs::leg_accounts, error::EscrowError, transfer_fee::gross_up, BundleEscrow, Config,
+    FeeSide,
+};
+
+/// Remaining accounts: one `[mint, taker_ata, maker_ata, token_program]` group
+/// per requested leg, followed by one `[mint, vault, taker_ata, token_program]`
+/// group per offered leg, each in the order stored on the bundle. Missing
+/// destination ATAs are created at the taker's expense. While the protocol
+/// fee is on, one treasury ATA per leg on the fee side follows, in the same
+/// order as those legs.
+#[derive(Accounts)]
+pub struct TakeBundle<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"bundle", maker.key().as_ref(), bundle.seed.to_le_bytes().as_ref()],
+        bump = bundle.bump,
+    )]
+    pub bundle: Account<'info, BundleEscrow>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeBundle<'info> {
+
+    /// Pays every requested leg to the maker and releases every offered vault
+    /// to the taker, cutting the protocol fee from each leg on the fee side.
+    /// Any failing leg reverts the whole swap.
+    pub fn take_bundle(&mut self, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let split = self.bundle.requested.len() * 4;
+        let legs_end = split + self.bundle.offered.len() * 4;
+        let (leg_groups, treasury_atas) = accounts.split_at(legs_end.min(accounts.len()));
+        let (requested_accounts, offered_accounts) =
+            leg_groups.split_at(split.min(leg_groups.len()));
+
+        let requested = leg_accounts(&self.bundle.requested, requested_accounts)?;
+        let offered = leg_accounts(&self.bundle.offered, offered_accounts)?;
+
+        let fee_legs = match self.config.fee_side {
+            FeeSide::MintB => requested.len(),
+            FeeSide::MintA => offered.len(),
+        };
+        let expected_treasuries = if self.config.fee_bps > 0 { fee_legs } else { 0 };
+        require_eq!(treasury_atas.len(), expected_treasuries, EscrowError::LegAccountsMismatch);
+
+        let taker = self.taker.to_account_info();
+        let maker = self.maker.to_account_info();
+        let bundle = self.bundle.to_account_info();
+        let associated_token_program = self.associated_token_program.to_account_info();
+        let system_program = self.system_program.to_account_info();
+
+        for (i, (leg, accounts)) in self.bundle.requested.iter().zip(&requested).enumerate() {
+            accounts.create_ata(
+                accounts.to,
+                &maker,
+                &taker,
+                &associated_token_program,
+                &system_program,
+            )?;
+
+            let (maker_amount, fee) = self.config.split_fee(FeeSide::MintB, leg.amount);
+            let gross = |amount| {
+                if self.bundle.gross_up {
+                    gross_up(accounts.mint, amount)
+                } else {
+                    Ok(amount)
+                }
+            };
+
+            accounts.transfer(&taker, gross(maker_amount)?, &[])?;
+
+            if fee > 0 {
+                accounts.require_ata(&treasury_atas[i], &self.config.treasury)?;
+                accounts.transfer_to(&treasury_atas[i], &taker, gross(fee)?, &[])?;
+            }
+        }
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"bundle",
+            self.maker.key.as_ref(),
+            &self.bundle.seed.to_le_bytes()[..],
+            &[self.bundle.bump],
+        ]];
+
+        for (i, (leg, accounts)) in self.bundle.offered.iter().zip(&offered).enumerate() {
+            accounts.require_ata(accounts.from, bundle.key)?;
+            accounts.create_ata(
+                accounts.to,
+                &taker,
+                &taker,
+                &associated_token_program,
+                &system_program,
+            )?;
+
+            // Release the vault's whole balance, so tokens donated to it do
+            // not keep it from being closed.
+            let vault = InterfaceAccount::<TokenAccount>::try_from(accounts.from)?;
+            let (_, fee) = self.config.split_fee(FeeSide::MintA, leg.amount);
+
+            let taker_amount = vault.amount.checked_sub(fee).ok_or(EscrowError::Overflow)?;
+
+            accounts.transfer(&bundle, taker_amount, signer_seeds)?;
+
+            if fee > 0 {
+                accounts.require_ata(&treasury_atas[i], &self.config.treasury)?;
+                accounts.transfer_to(&treasury_atas[i], &bundle, fee, signer_seeds)?;
+            }
+
+            accounts.close_from(&maker, &bundle, signer_seeds)?;
+        }
+
+        emit!(self.bundle.taken_event(self.bundle.key(), self.taker.key())?);
+
+        Ok(())
+    }
+}
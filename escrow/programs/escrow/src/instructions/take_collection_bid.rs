@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{Metadata, MetadataAccount},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::EscrowError, escrow_vault::release_fill, Config, Escrow, OrderBook};
+
+/// Sells one NFT into a collection bid. The NFT and mint_a may live under
+/// different token programs, so each side has its own.
+#[derive(Accounts)]
+pub struct TakeCollectionBid<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = nft_token_program,
+        constraint = nft_mint.decimals == 0 && nft_mint.supply == 1 @ EscrowError::CollectionMismatch,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: Box<Account<'info, MetadataAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = taker,
+        associated_token::token_program = nft_token_program
+    )]
+    pub taker_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = nft_token_program
+    )]
+    pub maker_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Treasury token account of mint_a, only needed when the take owes a fee.
+    #[account(
+        mut,
+        constraint = treasury_ata.owner == config.treasury @ EscrowError::InvalidTreasury,
+        constraint = treasury_ata.mint == mint_a.key() @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub nft_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeCollectionBid<'info> {
+
+    /// Checks the NFT's metadata against the bid and hands the NFT to the maker.
+    pub fn deliver_nft(&mut self, expected_revision: Option<u64>) -> Result<()> {
+        let Some(collection) = self.escrow.collection else {
+            return err!(EscrowError::CollectionMismatch);
+        };
+
+        self.escrow.check_revision(expected_revision)?;
+
+        require!(
+            self.nft_metadata
+                .collection
+                .as_ref()
+                .is_some_and(|c| c.verified && c.key == collection),
+            EscrowError::CollectionMismatch
+        );
+
+        self.escrow.check_fill(self.taker.key, self.escrow.fill_for(1)?)?;
+
+        let cpi_accounts = TransferChecked {
+            mint: self.nft_mint.to_account_info(),
+            from: self.taker_nft_ata.to_account_info(),
+            to: self.maker_nft_ata.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(self.nft_token_program.to_account_info(), cpi_accounts);
+
+        transfer_checked(cpi_context, 1, 0)
+    }
+
+    /// Releases the mint_a owed for one NFT, less the protocol fee. A bid's
+    /// only fungible leg is mint_a, so the fee is cut from it whatever the
+    /// configured fee side.
+    pub fn transfer_and_close_vault(&mut self) -> Result<()> {
+        let amount = self.escrow.fill_for(1)?;
+        let (_, fee) = self.config.split_fee(self.config.fee_side, amount);
+
+        emit!(self
+            .escrow
+            .taken_event(self.escrow.key(), self.taker.key(), amount, 1)?);
+
+        self.escrow.received += 1;
+
+        release_fill(
+            &mut self.escrow,
+            &self.mint_a,
+            &self.vault,
+            &self.maker,
+            self.taker_ata_a.to_account_info(),
+            self.treasury_ata.as_deref(),
+            &self.token_program,
+            amount,
+            fee,
+        )?;
+
+        self.book.update(
+            self.escrow.key(),
+            self.escrow.remaining(),
+            self.escrow.remaining_ask(),
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::EscrowError, escrow_vault::pay_mint_b, protocol_fee::treasury,
+    sol_vault::pay_from_sol_vault, Config, Escrow, FeeSide, OrderBook,
+};
+
+#[derive(Accounts)]
+pub struct TakeNativeOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_b,
+        constraint = escrow.is_native_offer() @ EscrowError::NotNativeLeg,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", escrow.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Treasury token account for a fee cut from mint_b, only needed when
+    /// the take owes one.
+    #[account(
+        mut,
+        constraint = treasury_ata.owner == config.treasury @ EscrowError::InvalidTreasury,
+        constraint = treasury_ata.mint == mint_b.key() @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Treasury wallet for a fee cut from the lamports, only needed when the
+    /// take owes one.
+    #[account(
+        mut,
+        address = config.treasury @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeNativeOffer<'info> {
+
+    pub fn deposit(&mut self, amount: u64, expected_revision: Option<u64>) -> Result<()> {
+        self.escrow.check_revision(expected_revision)?;
+        self.escrow.check_fill(self.taker.key, amount)?;
+
+        let price = self.escrow.price_for(amount)?;
+        let (maker_amount, fee) = self.config.split_fee(FeeSide::MintB, price);
+
+        self.pay_mint_b(self.maker_ata_b.to_account_info(), maker_amount)?;
+
+        if fee > 0 {
+            self.pay_mint_b(treasury(self.treasury_ata.as_deref())?, fee)?;
+        }
+
+        self.escrow.received += price;
+
+        emit!(self
+            .escrow
+            .taken_event(self.escrow.key(), self.taker.key(), amount, price)?);
+
+        Ok(())
+    }
+
+    fn pay_mint_b(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        pay_mint_b(
+            &self.escrow,
+            &self.mint_b,
+            &self.taker_ata_b,
+            &self.taker,
+            &self.token_program,
+            to,
+            amount,
+        )
+    }
+
+    /// Pays `amount` lamports to the taker, less the protocol fee when it is
+    /// cut from the lamports. Once the offer is completely filled, the rent
+    /// left in the SOL vault and the escrow go to the maker.
+    pub fn withdraw_and_close(&mut self, amount: u64, sol_vault_bump: u8) -> Result<()> {
+        let (taker_amount, fee) = self.config.split_fee(FeeSide::MintA, amount);
+
+        pay_from_sol_vault(
+            &self.escrow,
+            &self.sol_vault,
+            sol_vault_bump,
+            &self.taker.to_account_info(),
+            &self.system_program,
+            taker_amount,
+        )?;
+
+        if fee > 0 {
+            pay_from_sol_vault(
+                &self.escrow,
+                &self.sol_vault,
+                sol_vault_bump,
+                &treasury(self.treasury.as_ref())?,
+                &self.system_program,
+                fee,
+            )?;
+        }
+
+        self.escrow.filled += amount;
+
+        self.book.update(
+            self.escrow.key(),
+            self.escrow.remaining(),
+            self.escrow.remaining_ask(),
+        );
+
+        if !self.escrow.is_filled() {
+            return Ok(());
+        }
+
+        pay_from_sol_vault(
+            &self.escrow,
+            &self.sol_vault,
+            sol_vault_bump,
+            &self.maker.to_account_info(),
+            &self.system_program,
+            self.sol_vault.lamports(),
+        )?;
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+}
@@ -0,0 +1,153 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::EscrowError, escrow_vault::release_fill, protocol_fee::treasury, Config, Escrow,
+    FeeSide, OrderBook,
+};
+
+#[derive(Accounts)]
+pub struct TakeNativeRequest<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = maker,
+        constraint = escrow.is_native_request() @ EscrowError::NotNativeLeg,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Treasury token account for a fee cut from mint_a, only needed when
+    /// the take owes one.
+    #[account(
+        mut,
+        constraint = treasury_ata.owner == config.treasury @ EscrowError::InvalidTreasury,
+        constraint = treasury_ata.mint == mint_a.key() @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Treasury wallet for a fee cut from the lamports, only needed when the
+    /// take owes one.
+    #[account(
+        mut,
+        address = config.treasury @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump,
+        constraint = escrow.book_page == Some(book.page) @ EscrowError::WrongBookPage,
+    )]
+    pub book: Box<Account<'info, OrderBook>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeNativeRequest<'info> {
+
+    /// Pays the maker in lamports, straight from the taker's wallet.
+    pub fn deposit(&mut self, amount: u64, expected_revision: Option<u64>) -> Result<()> {
+        self.escrow.check_revision(expected_revision)?;
+        self.escrow.check_fill(self.taker.key, amount)?;
+
+        let price = self.escrow.price_for(amount)?;
+        let (maker_amount, fee) = self.config.split_fee(FeeSide::MintB, price);
+
+        self.pay_lamports(self.maker.to_account_info(), maker_amount)?;
+
+        if fee > 0 {
+            self.pay_lamports(treasury(self.treasury.as_ref())?, fee)?;
+        }
+
+        self.escrow.received += price;
+
+        emit!(self
+            .escrow
+            .taken_event(self.escrow.key(), self.taker.key(), amount, price)?);
+
+        Ok(())
+    }
+
+    fn pay_lamports(&self, to: AccountInfo<'info>, lamports: u64) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from: self.taker.to_account_info(),
+            to,
+        };
+
+        let cpi_context = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+
+        transfer(cpi_context, lamports)
+    }
+
+    /// Releases `amount` of mint_a to the taker and updates the offer's entry
+    /// in the book.
+    pub fn transfer_and_close_vault(&mut self, amount: u64) -> Result<()> {
+        let (_, fee) = self.config.split_fee(FeeSide::MintA, amount);
+
+        release_fill(
+            &mut self.escrow,
+            &self.mint_a,
+            &self.vault,
+            &self.maker,
+            self.taker_ata_a.to_account_info(),
+            self.treasury_ata.as_deref(),
+            &self.token_program,
+            amount,
+            fee,
+        )?;
+
+        self.book.update(
+            self.escrow.key(),
+            self.escrow.remaining(),
+            self.escrow.remaining_ask(),
+        );
+
+        Ok(())
+    }
+}
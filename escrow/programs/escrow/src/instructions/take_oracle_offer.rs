@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::EscrowError,
+    escrow_vault::{pay_mint_b, release_fill},
+    oracle_price::read_price,
+    protocol_fee::treasury,
+    transfer_fee::gross_up,
+    Config, Escrow, FeeSide, OraclePricing,
+};
+
+#[derive(Accounts)]
+pub struct TakeOracleOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Must be the feed recorded on the offer, parsed in `deposit`.
+    #[account(
+        constraint = escrow.oracle.is_some_and(|o| o.feed == price_account.key()) @ EscrowError::InvalidOracle,
+    )]
+    pub price_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = maker,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Treasury token account of the mint the fee is cut from, only needed
+    /// when the take owes a fee.
+    #[account(
+        mut,
+        constraint = treasury_ata.owner == config.treasury @ EscrowError::InvalidTreasury,
+        constraint = treasury_ata.mint == config.fee_mint(&escrow) @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeOracleOffer<'info> {
+    /// Pays the maker for `amount` of mint_a at the current oracle price.
+    /// `max_payment` caps what the taker is willing to pay in mint_b, since
+    /// the price can move between signing and landing.
+    pub fn deposit(
+        &mut self,
+        amount: u64,
+        max_payment: u64,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        let Some(pricing) = self.escrow.oracle else {
+            return err!(EscrowError::NotOracleOffer);
+        };
+
+        self.escrow.check_revision(expected_revision)?;
+        self.escrow.check_fill(self.taker.key, amount)?;
+
+        let price = read_price(pricing.source, &self.price_account, pricing.max_staleness)?;
+        let unit_price = pricing.unit_price(price, self.mint_b.decimals)?;
+        let payment = OraclePricing::price_for(amount, unit_price, self.mint_a.decimals)?;
+
+        require!(payment > 0, EscrowError::InvalidFillAmount);
+
+        let (maker_amount, fee) = self.config.split_fee(FeeSide::MintB, payment);
+
+        // The cap is on what actually leaves the taker, transfer fees included.
+        let total = if self.escrow.gross_up {
+            let mint_b = self.mint_b.to_account_info();
+
+            gross_up(&mint_b, maker_amount)?
+                .checked_add(if fee > 0 { gross_up(&mint_b, fee)? } else { 0 })
+                .ok_or(EscrowError::Overflow)?
+        } else {
+            payment
+        };
+
+        require!(total <= max_payment, EscrowError::SlippageExceeded);
+
+        self.pay_mint_b(self.maker_ata_b.to_account_info(), maker_amount)?;
+
+        if fee > 0 {
+            self.pay_mint_b(treasury(self.treasury_ata.as_deref())?, fee)?;
+        }
+
+        self.escrow.received += payment;
+
+        emit!(self
+            .escrow
+            .taken_event(self.escrow.key(), self.taker.key(), amount, payment)?);
+
+        Ok(())
+    }
+
+    fn pay_mint_b(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        pay_mint_b(
+            &self.escrow,
+            &self.mint_b,
+            &self.taker_ata_b,
+            &self.taker,
+            &self.token_program,
+            to,
+            amount,
+        )
+    }
+
+    /// Releases `amount` of mint_a to the taker.
+    pub fn transfer_and_close_vault(&mut self, amount: u64) -> Result<()> {
+        let (_, fee) = self.config.split_fee(FeeSide::MintA, amount);
+
+        release_fill(
+            &mut self.escrow,
+            &self.mint_a,
+            &self.vault,
+            &self.maker,
+            self.taker_ata_a.to_account_info(),
+            self.treasury_ata.as_deref(),
+            &self.token_program,
+            amount,
+            fee,
+        )
+    }
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::{Account as TokenAccountState, Mint},
+    },
+    token_2022_extensions::{harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint},
+};
+
+use crate::error::EscrowError;
+
+/// Amount to send so that exactly `net` arrives after the mint's current
+/// transfer fee. Mints without the Token-2022 transfer-fee extension return
+/// `net` unchanged.
+pub fn gross_up(mint: &AccountInfo, net: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(net);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    let Ok(config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(net);
+    };
+
+    let fee = config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, net)
+        .ok_or(EscrowError::Overflow)?;
+
+    Ok(net.checked_add(fee).ok_or(EscrowError::Overflow)?)
+}
+
+/// Moves the transfer fees withheld on `account` into its mint. Token-2022
+/// refuses to close an account that still withholds fees, so every vault of
+/// a transfer-fee mint goes through here before it is closed. `mint` must be
+/// writable; accounts that withhold nothing are left alone.
+pub fn harvest_withheld<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+) -> Result<()> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let withheld = {
+        let data = account.try_borrow_data()?;
+        let state = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+
+        state
+            .get_extension::<TransferFeeAmount>()
+            .map_or(0, |amount| u64::from(amount.withheld_amount))
+    };
+
+    if withheld == 0 {
+        return Ok(());
+    }
+
+    let cpi_accounts = HarvestWithheldTokensToMint {
+        token_program_id: token_program.clone(),
+        mint: mint.clone(),
+    };
+
+    harvest_withheld_tokens_to_mint(
+        CpiContext::new(token_program.clone(), cpi_accounts),
+        vec![account.clone()],
+    )
+}
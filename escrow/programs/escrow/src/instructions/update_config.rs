@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::EscrowError, Config, FeeSide, MAX_FEE_BPS};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ EscrowError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateConfig<'info> {
+
+    pub fn update_config(&mut self, fee_bps: u16, fee_side: FeeSide, treasury: Pubkey) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::FeeTooHigh);
+
+        self.config.fee_bps = fee_bps;
+        self.config.fee_side = fee_side;
+        self.config.treasury = treasury;
+
+        Ok(())
+    }
+}
@@ -1,11 +1,17 @@
+#![allow(unexpected_cfgs)]
+#![allow(deprecated)]
+
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+pub use error::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -15,18 +21,199 @@ declare_id!("8UD5djsyYztmVYQzaaSDJi6X8RRkYkzeXhGsihYDkGmf");
 pub mod escrow {
     use super::*;
 
-        pub fn make(ctx: Context<Make>, seed: u64, amount: u64, receive: u64) -> Result<()> {
-        ctx.accounts.init_escrow(seed, &ctx.bumps, receive)?;
-        ctx.accounts.deposit(amount)
+        pub fn init_config(
+        ctx: Context<InitConfig>,
+        fee_bps: u16,
+        fee_side: FeeSide,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.init_config(&ctx.bumps, fee_bps, fee_side, treasury)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: u16,
+        fee_side: FeeSide,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.update_config(fee_bps, fee_side, treasury)
+    }
+
+    pub fn init_book(ctx: Context<InitBook>, page: u32) -> Result<()> {
+        ctx.accounts.init_book(page, &ctx.bumps)
+    }
+
+    pub fn prune_book<'info>(ctx: Context<'_, '_, 'info, 'info, PruneBook<'info>>) -> Result<()> {
+        ctx.accounts.prune(ctx.remaining_accounts)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        amount: u64,
+        receive: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+        gross_up: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, &ctx.bumps, receive, expiry, tip, taker, gross_up)?;
+        ctx.accounts.deposit(amount)?;
+        ctx.accounts.deposit_tip()?;
+        ctx.accounts.list_offer()
     }
 
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         ctx.accounts.refund_and_close_vault()
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
+    pub fn take(ctx: Context<Take>, amount: u64, expected_revision: Option<u64>) -> Result<()> {
+        ctx.accounts.deposit(amount, expected_revision)?;
+        ctx.accounts.transfer_and_close_vault(amount)
+    }
+
+    pub fn amend(ctx: Context<Amend>, receive: u64, amount: u64) -> Result<()> {
+        ctx.accounts.amend(receive, amount)
+    }
+
+    pub fn expire(ctx: Context<Expire>) -> Result<()> {
+        ctx.accounts.return_and_close_vault()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_native_offer(
+        ctx: Context<MakeNativeOffer>,
+        seed: u64,
+        lamports: u64,
+        receive: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+        gross_up: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, &ctx.bumps, lamports, receive, expiry, tip, taker, gross_up)?;
+        ctx.accounts.deposit()?;
+        ctx.accounts.list_offer()
+    }
+
+    pub fn take_native_offer(
+        ctx: Context<TakeNativeOffer>,
+        amount: u64,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.deposit(amount, expected_revision)?;
+        ctx.accounts.withdraw_and_close(amount, ctx.bumps.sol_vault)
+    }
+
+    pub fn refund_native_offer(ctx: Context<RefundNativeOffer>) -> Result<()> {
+        ctx.accounts.refund_and_close_vault(ctx.bumps.sol_vault)
+    }
+
+    pub fn expire_native_offer(ctx: Context<ExpireNativeOffer>) -> Result<()> {
+        ctx.accounts.return_and_close_vault(ctx.bumps.sol_vault)
+    }
+
+    pub fn make_native_request(
+        ctx: Context<MakeNativeRequest>,
+        seed: u64,
+        amount: u64,
+        lamports: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, &ctx.bumps, amount, lamports, expiry, tip, taker)?;
         ctx.accounts.deposit()?;
+        ctx.accounts.list_offer()
+    }
+
+    pub fn take_native_request(
+        ctx: Context<TakeNativeRequest>,
+        amount: u64,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.deposit(amount, expected_revision)?;
+        ctx.accounts.transfer_and_close_vault(amount)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_oracle_offer(
+        ctx: Context<MakeOracleOffer>,
+        seed: u64,
+        amount: u64,
+        pricing: OraclePricing,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+        gross_up: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, &ctx.bumps, pricing, expiry, tip, taker, gross_up)?;
+        ctx.accounts.deposit(amount)
+    }
+
+    pub fn take_oracle_offer(
+        ctx: Context<TakeOracleOffer>,
+        amount: u64,
+        max_payment: u64,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.deposit(amount, max_payment, expected_revision)?;
+        ctx.accounts.transfer_and_close_vault(amount)
+    }
+
+    pub fn make_collection_bid(
+        ctx: Context<MakeCollectionBid>,
+        seed: u64,
+        amount: u64,
+        count: u64,
+        expiry: Option<i64>,
+        tip: u64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, &ctx.bumps, count, expiry, tip, taker)?;
+        ctx.accounts.deposit(amount)?;
+        ctx.accounts.list_offer()
+    }
+
+    pub fn take_collection_bid(
+        ctx: Context<TakeCollectionBid>,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.deliver_nft(expected_revision)?;
         ctx.accounts.transfer_and_close_vault()
     }
+
+    pub fn make_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeBundle<'info>>,
+        seed: u64,
+        offered: Vec<Leg>,
+        requested: Vec<Leg>,
+        gross_up: bool,
+    ) -> Result<()> {
+        ctx.accounts.make_bundle(
+            seed,
+            offered,
+            requested,
+            gross_up,
+            &ctx.bumps,
+            ctx.remaining_accounts,
+        )
+    }
+
+    pub fn take_bundle<'info>(ctx: Context<'_, '_, 'info, 'info, TakeBundle<'info>>) -> Result<()> {
+        ctx.accounts.take_bundle(ctx.remaining_accounts)
+    }
+
+    pub fn refund_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundBundle<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.refund_bundle(ctx.remaining_accounts)
+    }
     
 }
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::EscrowError, BundleMade, BundleRefunded, BundleTaken, MAX_OFFERED_LEGS,
+    MAX_REQUESTED_LEGS,
+};
+
+/// Escrow of a basket of mints against another basket. Every offered leg sits
+/// in its own ATA owned by this account and the swap settles in one `take_bundle`.
+#[account]
+#[derive(InitSpace)]
+pub struct BundleEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    #[max_len(MAX_OFFERED_LEGS)]
+    pub offered: Vec<Leg>,
+    #[max_len(MAX_REQUESTED_LEGS)]
+    pub requested: Vec<Leg>,
+    /// Takers pay enough extra on every requested leg to cover its transfer
+    /// fee, so the maker receives the listed amounts net.
+    pub gross_up: bool,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Leg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl BundleEscrow {
+    /// Checks one side of a bundle: between one and `max` legs, each with a
+    /// non-zero amount and a mint that appears only once.
+    pub fn check_legs(legs: &[Leg], max: usize) -> Result<()> {
+        require!(!legs.is_empty() && legs.len() <= max, EscrowError::InvalidBundle);
+
+        for (i, leg) in legs.iter().enumerate() {
+            require!(leg.amount > 0, EscrowError::InvalidBundle);
+            require!(
+                legs[..i].iter().all(|other| other.mint != leg.mint),
+                EscrowError::InvalidBundle
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn made_event(&self, key: Pubkey) -> Result<BundleMade> {
+        Ok(BundleMade {
+            bundle: key,
+            seed: self.seed,
+            maker: self.maker,
+            offered: self.offered.clone(),
+            requested: self.requested.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        })
+    }
+
+    pub fn taken_event(&self, key: Pubkey, taker: Pubkey) -> Result<BundleTaken> {
+        Ok(BundleTaken {
+            bundle: key,
+            seed: self.seed,
+            maker: self.maker,
+            taker,
+            offered: self.offered.clone(),
+            requested: self.requested.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        })
+    }
+
+    pub fn refunded_event(&self, key: Pubkey) -> Result<BundleRefunded> {
+        Ok(BundleRefunded {
+            bundle: key,
+            seed: self.seed,
+            maker: self.maker,
+            offered: self.offered.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        })
+    }
+}
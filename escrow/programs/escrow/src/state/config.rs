@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::Escrow;
+
+/// Venue-wide settings, a single PDA at `[b"config"]`.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Owner of the ATAs that collect the protocol fee.
+    pub treasury: Pubkey,
+    pub fee_bps: u16,
+    pub fee_side: FeeSide,
+    pub bump: u8,
+}
+
+/// Which leg of a take the protocol fee is cut from.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum FeeSide {
+    /// Cut from the mint_a released to the taker.
+    MintA,
+    /// Cut from the mint_b paid to the maker.
+    MintB,
+}
+
+impl Config {
+    pub fn fee_mint(&self, escrow: &Escrow) -> Pubkey {
+        match self.fee_side {
+            FeeSide::MintA => escrow.mint_a,
+            FeeSide::MintB => escrow.mint_b,
+        }
+    }
+
+    /// Fee owed on `amount` of the leg the fee is cut from, rounded down.
+    pub fn fee_on(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+
+    /// Splits `amount` of the leg on `side` into what its recipient gets and
+    /// the protocol fee cut from it. Only the leg on `fee_side` pays a fee.
+    pub fn split_fee(&self, side: FeeSide, amount: u64) -> (u64, u64) {
+        if side != self.fee_side {
+            return (amount, 0);
+        }
+
+        let fee = self.fee_on(amount);
+
+        (amount - fee, fee)
+    }
+}
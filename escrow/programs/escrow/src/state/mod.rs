@@ -1,5 +1,17 @@
+pub mod bundle;
+pub mod config;
+pub mod oracle;
+pub mod order_book;
+
+pub use bundle::*;
+pub use config::*;
+pub use oracle::*;
+pub use order_book::*;
+
 use anchor_lang::prelude::*;
 
+use crate::{error::EscrowError, OfferMade, OfferRefunded, OfferTaken, NATIVE_SOL};
+
 #[account]
 #[derive(InitSpace)]
 pub struct Escrow{
@@ -7,6 +19,190 @@ pub struct Escrow{
     pub maker: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
+    /// Total amount of mint_b asked for the whole offer.
     pub recieve: u64,
-    pub bump: u8
-}
\ No newline at end of file
+    pub bump: u8,
+    /// Total amount of mint_a that reached the vault, net of any transfer fee.
+    pub amount: u64,
+    /// Amount of mint_a already released to takers.
+    pub filled: u64,
+    /// Amount of mint_b already paid to the maker.
+    pub received: u64,
+    /// Unix timestamp after which the offer can no longer be taken.
+    pub expiry: Option<i64>,
+    /// Lamports held on the escrow account for whoever calls `expire`.
+    pub tip: u64,
+    /// Only this key may take the offer when set.
+    pub taker: Option<Pubkey>,
+    /// Takers pay enough extra mint_b to cover its transfer fee, so the maker
+    /// receives the agreed amount net.
+    pub gross_up: bool,
+    /// Bumped by every `amend`, so a taker can pin the terms they accepted.
+    pub revision: u64,
+    /// Set on collection bids: any NFT verified as a member of this
+    /// collection settles one unit of `recieve`, and `mint_b` holds the
+    /// collection mint.
+    pub collection: Option<Pubkey>,
+    /// Set on oracle offers, which are priced at take time and leave
+    /// `recieve` at zero.
+    pub oracle: Option<OraclePricing>,
+    /// Page of the pair's order book the offer is listed on. Oracle offers
+    /// have no fixed price and are not listed.
+    pub book_page: Option<u32>,
+}
+
+impl Escrow {
+    /// Checks the terms a maker sets when opening an offer.
+    pub fn check_terms(recieve: u64, expiry: Option<i64>) -> Result<()> {
+        require!(recieve > 0, EscrowError::InvalidAmount);
+
+        Self::check_expiry(expiry)
+    }
+
+    pub fn check_expiry(expiry: Option<i64>) -> Result<()> {
+        if let Some(expiry) = expiry {
+            require!(expiry > Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `taker` may fill `amount` of the offer right now.
+    pub fn check_fill(&self, taker: &Pubkey, amount: u64) -> Result<()> {
+        require!(self.can_be_taken_by(taker), EscrowError::UnauthorizedTaker);
+        require!(
+            !self.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::OfferExpired
+        );
+        require!(
+            amount > 0 && amount <= self.remaining(),
+            EscrowError::InvalidFillAmount
+        );
+
+        Ok(())
+    }
+
+    pub fn check_revision(&self, expected_revision: Option<u64>) -> Result<()> {
+        if let Some(expected) = expected_revision {
+            require_eq!(self.revision, expected, EscrowError::RevisionMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Amount of mint_a released for `count` more units of `recieve`, so that
+    /// the last unit takes whatever is left in the vault.
+    pub fn fill_for(&self, count: u64) -> Result<u64> {
+        let received = self.received.checked_add(count).ok_or(EscrowError::Overflow)?;
+        require!(received <= self.recieve, EscrowError::InvalidFillAmount);
+
+        let owed = received as u128 * self.amount as u128 / self.recieve as u128;
+
+        Ok(owed as u64 - self.filled)
+    }
+
+    /// Amount of mint_a to move out of a vault holding `vault_balance` for a
+    /// fill of `amount`. The fill that completes the offer empties the vault,
+    /// so tokens donated to it cannot keep it from being closed.
+    pub fn release_for(&self, amount: u64, vault_balance: u64) -> u64 {
+        if self.filled + amount == self.amount {
+            vault_balance.max(amount)
+        } else {
+            amount
+        }
+    }
+
+    /// The offered side is lamports held in the `[b"sol_vault", escrow]` PDA.
+    pub fn is_native_offer(&self) -> bool {
+        self.mint_a == NATIVE_SOL
+    }
+
+    /// The requested side is lamports paid straight to the maker.
+    pub fn is_native_request(&self) -> bool {
+        self.mint_b == NATIVE_SOL
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.amount - self.filled
+    }
+
+    /// Amount of mint_b still owed for the rest of the offer.
+    pub fn remaining_ask(&self) -> u64 {
+        self.recieve.saturating_sub(self.received)
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.filled == self.amount
+    }
+
+    pub fn can_be_taken_by(&self, taker: &Pubkey) -> bool {
+        self.taker.is_none_or(|allowed| allowed == *taker)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry.is_some_and(|expiry| now >= expiry)
+    }
+
+    /// `OfferMade` for this offer, stored at `key`, once its vault is funded.
+    pub fn made_event(&self, key: Pubkey) -> Result<OfferMade> {
+        Ok(OfferMade {
+            escrow: key,
+            seed: self.seed,
+            maker: self.maker,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            amount: self.amount,
+            receive: self.recieve,
+            timestamp: Clock::get()?.unix_timestamp,
+        })
+    }
+
+    /// `OfferTaken` for a take of `amount` of mint_a against `paid`, built
+    /// before the fill is recorded.
+    pub fn taken_event(
+        &self,
+        key: Pubkey,
+        taker: Pubkey,
+        amount: u64,
+        paid: u64,
+    ) -> Result<OfferTaken> {
+        Ok(OfferTaken {
+            escrow: key,
+            seed: self.seed,
+            maker: self.maker,
+            taker,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            amount,
+            paid,
+            remaining: self.remaining() - amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        })
+    }
+
+    /// `OfferRefunded` for `amount` of mint_a handed back to the maker.
+    pub fn refunded_event(&self, key: Pubkey, amount: u64) -> Result<OfferRefunded> {
+        Ok(OfferRefunded {
+            escrow: key,
+            seed: self.seed,
+            maker: self.maker,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        })
+    }
+
+    /// Amount of mint_b owed for releasing `fill` more of mint_a at the
+    /// maker's price of `recieve / amount`. The price is applied to the
+    /// cumulative fill and rounded up, so splitting an offer into many small
+    /// takes never pays the maker less than one take of the whole size would.
+    pub fn price_for(&self, fill: u64) -> Result<u64> {
+        let filled = self.filled.checked_add(fill).ok_or(EscrowError::Overflow)?;
+
+        let owed = (filled as u128 * self.recieve as u128).div_ceil(self.amount as u128);
+        let owed = u64::try_from(owed).map_err(|_| EscrowError::Overflow)?;
+
+        Ok(owed - self.received)
+    }
+}
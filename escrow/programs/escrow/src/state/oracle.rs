@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::EscrowError, MAX_SPREAD_BPS};
+
+/// Terms of an offer priced off an oracle instead of a fixed `recieve`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct OraclePricing {
+    pub source: OracleSource,
+    /// Price account read on every take.
+    pub feed: Pubkey,
+    /// Added to the oracle price, in basis points. Negative values sell
+    /// below the oracle.
+    pub spread_bps: i16,
+    /// Lowest and highest price the maker accepts, in mint_b base units per
+    /// whole mint_a token. Takes are rejected outside the band.
+    pub min_price: u64,
+    pub max_price: u64,
+    /// Oldest oracle result, in slots, a take may settle against.
+    pub max_staleness: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum OracleSource {
+    /// A Switchboard on-demand pull feed. Only sources that record when they
+    /// were last updated are accepted, so `max_staleness` always applies.
+    Switchboard,
+}
+
+/// Oracle price of one whole mint_a token in whole mint_b tokens, as
+/// `mantissa / 10^scale`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub mantissa: u128,
+    pub scale: u32,
+}
+
+impl OraclePricing {
+    pub fn check(&self) -> Result<()> {
+        require!(
+            self.spread_bps.unsigned_abs() <= MAX_SPREAD_BPS,
+            EscrowError::InvalidPriceBand
+        );
+        require!(
+            self.min_price > 0 && self.min_price <= self.max_price,
+            EscrowError::InvalidPriceBand
+        );
+
+        require!(self.max_staleness > 0, EscrowError::InvalidPriceBand);
+
+        Ok(())
+    }
+
+    /// Price in mint_b base units per whole mint_a token once the spread is
+    /// applied, rounded up. Fails if it falls outside the maker's band.
+    pub fn unit_price(&self, price: OraclePrice, decimals_b: u8) -> Result<u64> {
+        let spread = (10_000 + self.spread_bps as i32) as u128;
+
+        let unit = price
+            .mantissa
+            .checked_mul(10u128.pow(decimals_b as u32))
+            .and_then(|v| v.checked_mul(spread))
+            .ok_or(EscrowError::Overflow)?
+            .div_ceil(10u128.pow(price.scale) * 10_000);
+
+        require!(
+            unit >= self.min_price as u128 && unit <= self.max_price as u128,
+            EscrowError::PriceOutOfBounds
+        );
+
+        Ok(unit as u64)
+    }
+
+    /// Amount of mint_b owed for `fill` base units of mint_a at `unit_price`,
+    /// rounded up in the maker's favour.
+    pub fn price_for(fill: u64, unit_price: u64, decimals_a: u8) -> Result<u64> {
+        let owed = (fill as u128 * unit_price as u128).div_ceil(10u128.pow(decimals_a as u32));
+
+        u64::try_from(owed).map_err(|_| error!(EscrowError::Overflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pricing(spread_bps: i16, min_price: u64, max_price: u64) -> OraclePricing {
+        OraclePricing {
+            source: OracleSource::Switchboard,
+            feed: Pubkey::new_unique(),
+            spread_bps,
+            min_price,
+            max_price,
+            max_staleness: 25,
+        }
+    }
+
+    // 1.5 whole mint_b per whole mint_a.
+    const PRICE: OraclePrice = OraclePrice {
+        mantissa: 15,
+        scale: 1,
+    };
+
+    #[test]
+    fn unit_price_applies_the_spread() {
+        assert_eq!(pricing(0, 1, u64::MAX).unit_price(PRICE, 6).unwrap(), 1_500_000);
+        assert_eq!(pricing(100, 1, u64::MAX).unit_price(PRICE, 6).unwrap(), 1_515_000);
+        assert_eq!(pricing(-100, 1, u64::MAX).unit_price(PRICE, 6).unwrap(), 1_485_000);
+    }
+
+    #[test]
+    fn unit_price_rounds_up() {
+        // 1.5 * 1.0001 = 1.50015 whole mint_b, with two decimals.
+        assert_eq!(pricing(1, 1, u64::MAX).unit_price(PRICE, 2).unwrap(), 151);
+    }
+
+    #[test]
+    fn unit_price_respects_the_band() {
+        assert!(pricing(0, 1_500_000, 1_500_000).unit_price(PRICE, 6).is_ok());
+        assert_eq!(
+            pricing(0, 1_500_001, u64::MAX).unit_price(PRICE, 6).unwrap_err(),
+            EscrowError::PriceOutOfBounds.into()
+        );
+        assert_eq!(
+            pricing(0, 1, 1_499_999).unit_price(PRICE, 6).unwrap_err(),
+            EscrowError::PriceOutOfBounds.into()
+        );
+    }
+
+    #[test]
+    fn price_for_rounds_up_in_the_makers_favour() {
+        // Half a token at 1.5 is exactly 0.75.
+        assert_eq!(OraclePricing::price_for(500_000, 1_500_000, 6).unwrap(), 750_000);
+        // One base unit at 1.5 is 1.5 base units, rounded up.
+        assert_eq!(OraclePricing::price_for(1, 1_500_000, 6).unwrap(), 2);
+        assert_eq!(OraclePricing::price_for(0, 1_500_000, 6).unwrap(), 0);
+    }
+
+    #[test]
+    fn price_for_rejects_overflow() {
+        assert_eq!(
+            OraclePricing::price_for(u64::MAX, u64::MAX, 0).unwrap_err(),
+            EscrowError::Overflow.into()
+        );
+    }
+}
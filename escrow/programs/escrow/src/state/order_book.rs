@@ -0,0 +1,140 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{error::EscrowError, Escrow, BOOK_PAGE_ENTRIES};
+
+/// One page of open offers for a `(mint_a, mint_b)` pair, at
+/// `[b"book", mint_a, mint_b, page]`, cheapest first. A page starts empty and
+/// grows by one entry per listing, up to `BOOK_PAGE_ENTRIES`; a busy pair
+/// simply opens more pages.
+#[account]
+pub struct OrderBook {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub page: u32,
+    pub bump: u8,
+    pub entries: Vec<BookEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct BookEntry {
+    pub escrow: Pubkey,
+    /// Amount of mint_a still on offer.
+    pub size: u64,
+    /// Amount of mint_b asked for the whole `size`, so the price is `ask / size`.
+    pub ask: u64,
+}
+
+impl BookEntry {
+    fn cheaper_than(&self, other: &BookEntry) -> bool {
+        (self.ask as u128) * (other.size as u128) < (other.ask as u128) * (self.size as u128)
+    }
+}
+
+impl OrderBook {
+    /// Account size, discriminator included, for a page holding `entries`.
+    pub fn space(entries: usize) -> usize {
+        8 + 32 + 32 + 4 + 1 + 4 + entries * BookEntry::INIT_SPACE
+    }
+
+    /// Inserts `entry` behind every offer at the same or a better price.
+    pub fn insert(&mut self, entry: BookEntry) {
+        let index = self
+            .entries
+            .iter()
+            .position(|other| entry.cheaper_than(other))
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(index, entry);
+    }
+
+    /// Removes the entry of `escrow`, if it is listed.
+    pub fn remove(&mut self, escrow: &Pubkey) {
+        self.entries.retain(|entry| entry.escrow != *escrow);
+    }
+
+    /// Replaces the entry of `escrow` with its new size and ask, or drops it
+    /// once nothing is left on offer.
+    pub fn update(&mut self, escrow: Pubkey, size: u64, ask: u64) {
+        self.remove(&escrow);
+
+        if size > 0 {
+            self.insert(BookEntry { escrow, size, ask });
+        }
+    }
+}
+
+/// Lists a freshly made `escrow` in `book`, which must be the page recorded
+/// on the escrow. The page grows to fit the entry, with the extra rent paid
+/// by `payer`; a full page is rejected and the maker lists on another one.
+pub fn list_offer<'info>(
+    book: &mut Account<'info, OrderBook>,
+    escrow: &Account<'info, Escrow>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(
+        escrow.book_page == Some(book.page),
+        EscrowError::WrongBookPage
+    );
+
+    // An escrow re-made at the address of a closed one replaces its entry.
+    book.remove(&escrow.key());
+
+    require!(
+        book.entries.len() < BOOK_PAGE_ENTRIES,
+        EscrowError::BookPageFull
+    );
+
+    reserve_entry(book, payer, system_program)?;
+
+    book.insert(BookEntry {
+        escrow: escrow.key(),
+        size: escrow.remaining(),
+        ask: escrow.remaining_ask(),
+    });
+
+    Ok(())
+}
+
+/// Drops `escrow` from `book` when an offer is refunded or expired. The book
+/// is optional on those paths so that a full or missing page can never keep
+/// the maker's funds locked; when it is left out, `prune_book` clears the
+/// entry later.
+pub fn unlist_offer(book: Option<&mut Account<OrderBook>>, escrow: &Pubkey) {
+    if let Some(book) = book {
+        book.remove(escrow);
+    }
+}
+
+fn reserve_entry<'info>(
+    book: &Account<'info, OrderBook>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let info = book.to_account_info();
+    let needed = OrderBook::space(book.entries.len() + 1);
+
+    if info.data_len() >= needed {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(needed);
+
+    if rent > info.lamports() {
+        let cpi_accounts = Transfer {
+            from: payer.clone(),
+            to: info.clone(),
+        };
+
+        let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+
+        transfer(cpi_ctx, rent - info.lamports())?;
+    }
+
+    info.resize(needed)?;
+
+    Ok(())
+}
@@ -7,6 +7,7 @@ import {
   PublicKey,
   SystemProgram
 } from "@solana/web3.js";
+import { assert } from "chai";
 import { Escrow } from "../target/types/escrow";
 
 describe("escrow", () => {
@@ -66,6 +67,26 @@ describe("escrow", () => {
     tokenProgram
   );
 
+  const [treasuryAtaA, treasuryAtaB] = [mintA, mintB].map((m) =>
+    spl.getAssociatedTokenAddressSync(
+      m.publicKey,
+      provider.publicKey,
+      false,
+      tokenProgram
+    )
+  );
+
+  const bookFor = (a: PublicKey, b: PublicKey, page = 0) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("book"),
+        a.toBuffer(),
+        b.toBuffer(),
+        new anchor.BN(page).toArrayLike(Buffer, "le", 4),
+      ],
+      programId
+    )[0];
+
   const accounts = {
     maker: maker.publicKey,
     taker: taker.publicKey,
@@ -77,6 +98,8 @@ describe("escrow", () => {
     takerAtaB,
     escrow,
     vault,
+    treasuryAta: treasuryAtaB,
+    book: bookFor(mintA.publicKey, mintB.publicKey),
     tokenProgram,
   };
 
@@ -147,9 +170,64 @@ describe("escrow", () => {
       .then(log);
   });
 
+  it("Initialize config", async () => {
+    const programData = PublicKey.findProgramAddressSync(
+      [programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    )[0];
+
+    await program.methods
+      .initConfig(0, { mintB: {} }, provider.publicKey)
+      .accounts({ admin: provider.publicKey, programData })
+      .rpc()
+      .then(confirm);
+
+    const tx = new anchor.web3.Transaction().add(
+      ...[
+        { mint: mintA.publicKey, ata: treasuryAtaA },
+        { mint: mintB.publicKey, ata: treasuryAtaB },
+      ].map((x) =>
+        spl.createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          x.ata,
+          provider.publicKey,
+          x.mint,
+          tokenProgram
+        )
+      )
+    );
+    await provider.sendAndConfirm(tx);
+
+    try {
+      await program.methods
+        .updateConfig(10_000, { mintB: {} }, provider.publicKey)
+        .accounts({ admin: provider.publicKey })
+        .rpc();
+      assert.fail("fee above the maximum should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "FeeTooHigh");
+    }
+  });
+
+  it("Open order books", async () => {
+    const native = SystemProgram.programId;
+
+    for (const [a, b] of [
+      [mintA.publicKey, mintB.publicKey],
+      [native, mintB.publicKey],
+      [mintA.publicKey, native],
+    ]) {
+      await program.methods
+        .initBook(0)
+        .accounts({ payer: provider.publicKey, mintA: a, mintB: b })
+        .rpc()
+        .then(confirm);
+    }
+  });
+
   it("Make escrow", async () => {
     await program.methods
-      .make(SEED, new anchor.BN(1e6), new anchor.BN(1e6))
+      .make(SEED, new anchor.BN(1e6), new anchor.BN(1e6), null, new anchor.BN(0), null, false)
       .accounts({ ...accounts })
       .signers([maker])
       .rpc()
@@ -161,7 +239,7 @@ describe("escrow", () => {
 
   it("Take escrow", async () => {
     await program.methods
-      .take()
+      .take(new anchor.BN(1e6), null)
       .accounts({ ...accounts })
       .signers([taker])
       .rpc()
@@ -171,6 +249,882 @@ describe("escrow", () => {
     console.log("✅ Escrow completed successfully");
   });
 
+  it("Partially fill escrow", async () => {
+    const partialSeed = new anchor.BN(3);
+    const partialEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        partialSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+
+    const partialAccounts = {
+      ...accounts,
+      escrow: partialEscrow,
+      vault: spl.getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        partialEscrow,
+        true,
+        tokenProgram
+      ),
+    };
+
+    // Offer 3 A for 2 B, then fill it in two takes.
+    await program.methods
+      .make(partialSeed, new anchor.BN(3e5), new anchor.BN(2e5), null, new anchor.BN(0), null, false)
+      .accounts(partialAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    const makerBBefore = await spl.getAccount(
+      provider.connection,
+      makerAtaB,
+      undefined,
+      tokenProgram
+    );
+
+    await program.methods
+      .take(new anchor.BN(1e5), null)
+      .accounts(partialAccounts)
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.escrow.fetch(partialEscrow);
+    assert.equal(state.filled.toNumber(), 1e5);
+    assert.equal(state.received.toNumber(), 66667);
+
+    const book = await program.account.orderBook.fetch(accounts.book);
+    const entry = book.entries.find((e) => e.escrow.equals(partialEscrow));
+    assert.equal(entry.size.toNumber(), 2e5);
+    assert.equal(entry.ask.toNumber(), 2e5 - 66667);
+
+    // Tokens sent straight to the vault go out with the last fill instead of
+    // blocking its close.
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        spl.createTransferCheckedInstruction(
+          makerAtaA,
+          mintA.publicKey,
+          partialAccounts.vault,
+          maker.publicKey,
+          1,
+          6,
+          [],
+          tokenProgram
+        )
+      ),
+      [maker]
+    );
+
+    await program.methods
+      .take(new anchor.BN(2e5), null)
+      .accounts(partialAccounts)
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    const makerBAfter = await spl.getAccount(
+      provider.connection,
+      makerAtaB,
+      undefined,
+      tokenProgram
+    );
+    assert.equal(Number(makerBAfter.amount - makerBBefore.amount), 2e5);
+    assert.isNull(await provider.connection.getAccountInfo(partialEscrow));
+    assert.isNull(await provider.connection.getAccountInfo(partialAccounts.vault));
+
+    console.log("✅ Escrow filled in two takes");
+  });
+
+  it("Expire escrow and tip the caller", async () => {
+    const expiringSeed = new anchor.BN(4);
+    const expiringEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        expiringSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+
+    const expiringAccounts = {
+      ...accounts,
+      escrow: expiringEscrow,
+      vault: spl.getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        expiringEscrow,
+        true,
+        tokenProgram
+      ),
+    };
+
+    const tip = 10_000;
+    const expiry = Math.floor(Date.now() / 1000) + 2;
+
+    await program.methods
+      .make(
+        expiringSeed,
+        new anchor.BN(1e5),
+        new anchor.BN(1e5),
+        new anchor.BN(expiry),
+        new anchor.BN(tip),
+        null,
+        false
+      )
+      .accounts(expiringAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    try {
+      await program.methods
+        .take(new anchor.BN(1e5), null)
+        .accounts(expiringAccounts)
+        .signers([taker])
+        .rpc();
+      assert.fail("take should reject an expired offer");
+    } catch (err) {
+      assert.include(err.toString(), "OfferExpired");
+    }
+
+    const makerABefore = await spl.getAccount(
+      provider.connection,
+      makerAtaA,
+      undefined,
+      tokenProgram
+    );
+    const callerBefore = await provider.connection.getBalance(taker.publicKey);
+
+    await program.methods
+      .expire()
+      .accounts({ ...expiringAccounts, caller: taker.publicKey })
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    const makerAAfter = await spl.getAccount(
+      provider.connection,
+      makerAtaA,
+      undefined,
+      tokenProgram
+    );
+    const callerAfter = await provider.connection.getBalance(taker.publicKey);
+
+    assert.equal(Number(makerAAfter.amount - makerABefore.amount), 1e5);
+    // The caller pays the transaction fee out of the tip.
+    assert.equal(callerAfter - callerBefore, tip - 5000);
+    assert.isNull(await provider.connection.getAccountInfo(expiringEscrow));
+
+    console.log("✅ Expired escrow cleaned up");
+  });
+
+  it("Private escrow only accepts the designated taker", async () => {
+    const escrowFor = (seed: anchor.BN) => {
+      const escrow = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("escrow"),
+          maker.publicKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        programId
+      )[0];
+
+      return {
+        ...accounts,
+        escrow,
+        vault: spl.getAssociatedTokenAddressSync(
+          mintA.publicKey,
+          escrow,
+          true,
+          tokenProgram
+        ),
+      };
+    };
+
+    const reserved = escrowFor(new anchor.BN(5));
+    const forTaker = escrowFor(new anchor.BN(6));
+
+    await program.methods
+      .make(new anchor.BN(5), new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), Keypair.generate().publicKey, false)
+      .accounts(reserved)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    try {
+      await program.methods
+        .take(new anchor.BN(1e5), null)
+        .accounts(reserved)
+        .signers([taker])
+        .rpc();
+      assert.fail("take should reject a taker the offer is not reserved for");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedTaker");
+    }
+
+    await program.methods
+      .refund()
+      .accounts(reserved)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .make(new anchor.BN(6), new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), taker.publicKey, false)
+      .accounts(forTaker)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .take(new anchor.BN(1e5), null)
+      .accounts(forTaker)
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    console.log("✅ Private escrow taken by its designated taker");
+  });
+
+  it("Make and take bundle escrow", async () => {
+    const payer = provider.wallet.payer;
+    const bundleSeed = new anchor.BN(7);
+    const bundle = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("bundle"),
+        maker.publicKey.toBuffer(),
+        bundleSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+
+    // A second offered mint next to mintA.
+    const mintC = await spl.createMint(
+      provider.connection,
+      payer,
+      maker.publicKey,
+      null,
+      0,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    const makerAtaC = await spl.createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      mintC,
+      maker.publicKey,
+      undefined,
+      tokenProgram
+    );
+    await spl.mintTo(provider.connection, payer, mintC, makerAtaC, maker, 1, [], undefined, tokenProgram);
+
+    const ata = (mint: PublicKey, owner: PublicKey) =>
+      spl.getAssociatedTokenAddressSync(mint, owner, true, tokenProgram);
+    const meta = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+    const leg = (mint: PublicKey, from: PublicKey, to: PublicKey) =>
+      [mint, from, to, tokenProgram].map(meta);
+
+    await program.methods
+      .makeBundle(
+        bundleSeed,
+        [
+          { mint: mintA.publicKey, amount: new anchor.BN(1e5) },
+          { mint: mintC, amount: new anchor.BN(1) },
+        ],
+        [{ mint: mintB.publicKey, amount: new anchor.BN(5e5) }],
+        false
+      )
+      .accounts({ maker: maker.publicKey })
+      .remainingAccounts([
+        ...leg(mintA.publicKey, makerAtaA, ata(mintA.publicKey, bundle)),
+        ...leg(mintC, makerAtaC, ata(mintC, bundle)),
+      ])
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .takeBundle()
+      .accounts({ taker: taker.publicKey, maker: maker.publicKey, bundle })
+      .remainingAccounts([
+        ...leg(mintB.publicKey, takerAtaB, makerAtaB),
+        ...leg(mintA.publicKey, ata(mintA.publicKey, bundle), takerAtaA),
+        ...leg(mintC, ata(mintC, bundle), ata(mintC, taker.publicKey)),
+      ])
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    const takerC = await spl.getAccount(
+      provider.connection,
+      ata(mintC, taker.publicKey),
+      undefined,
+      tokenProgram
+    );
+    assert.equal(Number(takerC.amount), 1);
+    assert.isNull(await provider.connection.getAccountInfo(bundle));
+    assert.isNull(await provider.connection.getAccountInfo(ata(mintA.publicKey, bundle)));
+
+    console.log("✅ Bundle escrow settled atomically");
+  });
+
+  it("Trade native SOL without wrapping", async () => {
+    const escrowFor = (seed: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("escrow"),
+          maker.publicKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        programId
+      )[0];
+
+    // Maker offers 0.1 SOL for mintB.
+    const offerSeed = new anchor.BN(8);
+    const offerEscrow = escrowFor(offerSeed);
+
+    await program.methods
+      .makeNativeOffer(offerSeed, new anchor.BN(LAMPORTS_PER_SOL / 10), new anchor.BN(1e5), null, new anchor.BN(0), null, false)
+      .accounts({
+        maker: maker.publicKey,
+        mintB: mintB.publicKey,
+        book: bookFor(SystemProgram.programId, mintB.publicKey),
+        tokenProgram,
+      })
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    const takerBefore = await provider.connection.getBalance(taker.publicKey);
+
+    await program.methods
+      .takeNativeOffer(new anchor.BN(LAMPORTS_PER_SOL / 10), null)
+      .accounts({
+        ...accounts,
+        escrow: offerEscrow,
+        book: bookFor(SystemProgram.programId, mintB.publicKey),
+      })
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    const takerAfter = await provider.connection.getBalance(taker.publicKey);
+    assert.equal(takerAfter - takerBefore, LAMPORTS_PER_SOL / 10 - 5000);
+    assert.isNull(await provider.connection.getAccountInfo(offerEscrow));
+
+    // Maker offers mintA for 0.05 SOL.
+    const requestSeed = new anchor.BN(9);
+    const requestEscrow = escrowFor(requestSeed);
+    const requestAccounts = {
+      ...accounts,
+      escrow: requestEscrow,
+      vault: spl.getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        requestEscrow,
+        true,
+        tokenProgram
+      ),
+      book: bookFor(mintA.publicKey, SystemProgram.programId),
+    };
+
+    await program.methods
+      .makeNativeRequest(requestSeed, new anchor.BN(1e5), new anchor.BN(LAMPORTS_PER_SOL / 20), null, new anchor.BN(0), null)
+      .accounts(requestAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    const makerBefore = await provider.connection.getBalance(maker.publicKey);
+
+    await program.methods
+      .takeNativeRequest(new anchor.BN(1e5), new anchor.BN(0))
+      .accounts({ ...requestAccounts, treasuryAta: null })
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    // The maker also gets back the rent of the escrow and its vault.
+    const makerAfter = await provider.connection.getBalance(maker.publicKey);
+    assert.isAtLeast(makerAfter - makerBefore, LAMPORTS_PER_SOL / 20);
+    assert.isNull(await provider.connection.getAccountInfo(requestEscrow));
+
+    console.log("✅ Native SOL offer and request settled");
+  });
+
+  it("Take escrow with a protocol fee", async () => {
+    const balance = async (ata: PublicKey) =>
+      Number((await spl.getAccount(provider.connection, ata, undefined, tokenProgram)).amount);
+
+    const takeWithFee = async (seed: anchor.BN, feeSide: object, treasuryAta: PublicKey) => {
+      await program.methods
+        .updateConfig(100, feeSide as any, provider.publicKey)
+        .accounts({ admin: provider.publicKey })
+        .rpc()
+        .then(confirm);
+
+      const feeEscrow = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("escrow"),
+          maker.publicKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        programId
+      )[0];
+      const feeAccounts = {
+        ...accounts,
+        escrow: feeEscrow,
+        vault: spl.getAssociatedTokenAddressSync(
+          mintA.publicKey,
+          feeEscrow,
+          true,
+          tokenProgram
+        ),
+        treasuryAta,
+      };
+
+      await program.methods
+        .make(seed, new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), null, false)
+        .accounts(feeAccounts)
+        .signers([maker])
+        .rpc()
+        .then(confirm);
+
+      await program.methods
+        .take(new anchor.BN(1e5), null)
+        .accounts(feeAccounts)
+        .signers([taker])
+        .rpc()
+        .then(confirm);
+    };
+
+    // 1% of the mint_b payment goes to the treasury.
+    const makerB = await balance(makerAtaB);
+    await takeWithFee(new anchor.BN(10), { mintB: {} }, treasuryAtaB);
+    assert.equal((await balance(makerAtaB)) - makerB, 99_000);
+    assert.equal(await balance(treasuryAtaB), 1_000);
+
+    // 1% of the released mint_a goes to the treasury.
+    const takerA = await balance(takerAtaA);
+    await takeWithFee(new anchor.BN(11), { mintA: {} }, treasuryAtaA);
+    assert.equal((await balance(takerAtaA)) - takerA, 99_000);
+    assert.equal(await balance(treasuryAtaA), 1_000);
+
+    // Native legs pay the fee in lamports, straight to the treasury wallet.
+    const treasury = Keypair.generate();
+    await program.methods
+      .updateConfig(100, { mintB: {} }, treasury.publicKey)
+      .accounts({ admin: provider.publicKey })
+      .rpc()
+      .then(confirm);
+
+    const nativeSeed = new anchor.BN(15);
+    const nativeEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        nativeSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+    const nativeAccounts = {
+      ...accounts,
+      escrow: nativeEscrow,
+      vault: spl.getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        nativeEscrow,
+        true,
+        tokenProgram
+      ),
+      treasuryAta: null,
+      treasury: treasury.publicKey,
+      book: bookFor(mintA.publicKey, SystemProgram.programId),
+    };
+
+    await program.methods
+      .makeNativeRequest(nativeSeed, new anchor.BN(1e5), new anchor.BN(LAMPORTS_PER_SOL / 5), null, new anchor.BN(0), null)
+      .accounts(nativeAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .takeNativeRequest(new anchor.BN(1e5), null)
+      .accounts(nativeAccounts)
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    assert.equal(
+      await provider.connection.getBalance(treasury.publicKey),
+      LAMPORTS_PER_SOL / 500
+    );
+
+    await program.methods
+      .updateConfig(0, { mintB: {} }, provider.publicKey)
+      .accounts({ admin: provider.publicKey })
+      .rpc()
+      .then(confirm);
+
+    console.log("✅ Protocol fee routed to the treasury");
+  });
+
+  it("Gross up payments in a transfer-fee mint", async () => {
+    const payer = provider.wallet.payer;
+    const feeMint = Keypair.generate();
+    const feeBps = 100;
+
+    // A Token-2022 mint charging 1% on every transfer, held by the taker.
+    const mintLen = spl.getMintLen([spl.ExtensionType.TransferFeeConfig]);
+    const createMintTx = new anchor.web3.Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: feeMint.publicKey,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+        space: mintLen,
+        programId: tokenProgram,
+      }),
+      spl.createInitializeTransferFeeConfigInstruction(
+        feeMint.publicKey,
+        provider.publicKey,
+        provider.publicKey,
+        feeBps,
+        BigInt(1e9),
+        tokenProgram
+      ),
+      spl.createInitializeMintInstruction(
+        feeMint.publicKey,
+        6,
+        provider.publicKey,
+        null,
+        tokenProgram
+      )
+    );
+    await provider.sendAndConfirm(createMintTx, [feeMint]);
+
+    const takerAtaFee = await spl.createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      feeMint.publicKey,
+      taker.publicKey,
+      undefined,
+      tokenProgram
+    );
+    await spl.mintTo(provider.connection, payer, feeMint.publicKey, takerAtaFee, payer, 1e9, [], undefined, tokenProgram);
+
+    await program.methods
+      .initBook(0)
+      .accounts({ payer: provider.publicKey, mintA: mintA.publicKey, mintB: feeMint.publicKey })
+      .rpc()
+      .then(confirm);
+
+    const feeSeed = new anchor.BN(12);
+    const feeEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        feeSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+    const makerAtaFee = spl.getAssociatedTokenAddressSync(
+      feeMint.publicKey,
+      maker.publicKey,
+      false,
+      tokenProgram
+    );
+    const treasuryAtaFee = await spl.createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      feeMint.publicKey,
+      provider.publicKey,
+      undefined,
+      tokenProgram
+    );
+
+    const feeAccounts = {
+      ...accounts,
+      mintB: feeMint.publicKey,
+      makerAtaB: makerAtaFee,
+      takerAtaB: takerAtaFee,
+      escrow: feeEscrow,
+      vault: spl.getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        feeEscrow,
+        true,
+        tokenProgram
+      ),
+      treasuryAta: treasuryAtaFee,
+      book: bookFor(mintA.publicKey, feeMint.publicKey),
+    };
+
+    await program.methods
+      .make(feeSeed, new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), null, true)
+      .accounts(feeAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .take(new anchor.BN(1e5), null)
+      .accounts(feeAccounts)
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    const makerFee = await spl.getAccount(
+      provider.connection,
+      makerAtaFee,
+      undefined,
+      tokenProgram
+    );
+    assert.equal(Number(makerFee.amount), 1e5);
+
+    console.log("✅ Maker received the exact net amount");
+  });
+
+  it("Close vaults of a transfer-fee mint_a", async () => {
+    const payer = provider.wallet.payer;
+    const feeMintA = Keypair.generate();
+
+    // Every deposit into the vault leaves a 1% fee withheld on it, which
+    // Token-2022 requires to be harvested before the vault can close.
+    const mintLen = spl.getMintLen([spl.ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: feeMintA.publicKey,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          space: mintLen,
+          programId: tokenProgram,
+        }),
+        spl.createInitializeTransferFeeConfigInstruction(
+          feeMintA.publicKey,
+          provider.publicKey,
+          provider.publicKey,
+          100,
+          BigInt(1e9),
+          tokenProgram
+        ),
+        spl.createInitializeMintInstruction(
+          feeMintA.publicKey,
+          6,
+          provider.publicKey,
+          null,
+          tokenProgram
+        )
+      ),
+      [feeMintA]
+    );
+
+    const makerAtaFeeA = await spl.createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      feeMintA.publicKey,
+      maker.publicKey,
+      undefined,
+      tokenProgram
+    );
+    await spl.mintTo(provider.connection, payer, feeMintA.publicKey, makerAtaFeeA, payer, 1e9, [], undefined, tokenProgram);
+
+    await program.methods
+      .initBook(0)
+      .accounts({ payer: provider.publicKey, mintA: feeMintA.publicKey, mintB: mintB.publicKey })
+      .rpc()
+      .then(confirm);
+
+    const feeAccountsFor = (seed: anchor.BN) => {
+      const feeEscrow = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("escrow"),
+          maker.publicKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        programId
+      )[0];
+
+      return {
+        ...accounts,
+        mintA: feeMintA.publicKey,
+        makerAtaA: makerAtaFeeA,
+        takerAtaA: spl.getAssociatedTokenAddressSync(
+          feeMintA.publicKey,
+          taker.publicKey,
+          false,
+          tokenProgram
+        ),
+        escrow: feeEscrow,
+        vault: spl.getAssociatedTokenAddressSync(
+          feeMintA.publicKey,
+          feeEscrow,
+          true,
+          tokenProgram
+        ),
+        treasuryAta: null,
+        book: bookFor(feeMintA.publicKey, mintB.publicKey),
+      };
+    };
+
+    // Filled in full: the last take closes the vault.
+    const takeAccounts = feeAccountsFor(new anchor.BN(16));
+
+    await program.methods
+      .make(new anchor.BN(16), new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), null, false)
+      .accounts(takeAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    const { amount } = await program.account.escrow.fetch(takeAccounts.escrow);
+    assert.equal(amount.toNumber(), 99_000);
+
+    await program.methods
+      .take(amount, null)
+      .accounts(takeAccounts)
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    assert.isNull(await provider.connection.getAccountInfo(takeAccounts.vault));
+    assert.isNull(await provider.connection.getAccountInfo(takeAccounts.escrow));
+
+    // Refunded: the vault closes back to the maker.
+    const refundAccounts = feeAccountsFor(new anchor.BN(17));
+
+    await program.methods
+      .make(new anchor.BN(17), new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), null, false)
+      .accounts(refundAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .refund()
+      .accounts(refundAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    assert.isNull(await provider.connection.getAccountInfo(refundAccounts.vault));
+    assert.isNull(await provider.connection.getAccountInfo(refundAccounts.escrow));
+
+    console.log("✅ Transfer-fee vaults harvested and closed");
+  });
+
+  it("Amend escrow and pin the revision", async () => {
+    const amendSeed = new anchor.BN(13);
+    const amendEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        amendSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+    const amendAccounts = {
+      ...accounts,
+      escrow: amendEscrow,
+      vault: spl.getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        amendEscrow,
+        true,
+        tokenProgram
+      ),
+    };
+
+    await program.methods
+      .make(amendSeed, new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), null, false)
+      .accounts(amendAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    // Double the size and ask for three times as much.
+    await program.methods
+      .amend(new anchor.BN(3e5), new anchor.BN(2e5))
+      .accounts(amendAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.escrow.fetch(amendEscrow);
+    assert.equal(state.revision.toNumber(), 1);
+    assert.equal(state.amount.toNumber(), 2e5);
+    assert.equal(state.recieve.toNumber(), 3e5);
+
+    try {
+      await program.methods
+        .take(new anchor.BN(2e5), new anchor.BN(0))
+        .accounts(amendAccounts)
+        .signers([taker])
+        .rpc();
+      assert.fail("take should reject a stale revision");
+    } catch (err) {
+      assert.include(err.toString(), "RevisionMismatch");
+    }
+
+    await program.methods
+      .take(new anchor.BN(2e5), new anchor.BN(1))
+      .accounts(amendAccounts)
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    console.log("✅ Amended escrow taken at the pinned revision");
+  });
+
+  it("Oracle offers need a supported price account", async () => {
+    const oracleSeed = new anchor.BN(14);
+    const oracleEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        oracleSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+
+    // A mint is not a Switchboard pull feed.
+    const pricing = {
+      source: { switchboard: {} },
+      feed: mintB.publicKey,
+      spreadBps: 50,
+      minPrice: new anchor.BN(1),
+      maxPrice: new anchor.BN(1e9),
+      maxStaleness: new anchor.BN(25),
+    };
+
+    try {
+      await program.methods
+        .makeOracleOffer(oracleSeed, new anchor.BN(1e5), pricing, null, new anchor.BN(0), null, false)
+        .accounts({
+          ...accounts,
+          priceAccount: mintB.publicKey,
+          escrow: oracleEscrow,
+          vault: spl.getAssociatedTokenAddressSync(
+            mintA.publicKey,
+            oracleEscrow,
+            true,
+            tokenProgram
+          ),
+        })
+        .signers([maker])
+        .rpc();
+      assert.fail("make_oracle_offer should reject a non-oracle account");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidOracle");
+    }
+
+    console.log("✅ Oracle offer rejected an unsupported price account");
+  });
+
   // Alternative test for refund flow
   it("Make and refund escrow", async () => {
     const newSeed = new anchor.BN(2);
@@ -198,7 +1152,7 @@ describe("escrow", () => {
 
     // Create escrow
     await program.methods
-      .make(newSeed, new anchor.BN(1e6), new anchor.BN(1e6))
+      .make(newSeed, new anchor.BN(1e6), new anchor.BN(1e6), null, new anchor.BN(0), null, false)
       .accounts(refundAccounts)
       .signers([maker])
       .rpc()
@@ -216,4 +1170,82 @@ describe("escrow", () => {
 
     console.log("✅ Escrow refunded successfully");
   });
+
+  it("List on a later book page and refund without it", async () => {
+    const pageSeed = new anchor.BN(18);
+    const pageEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        pageSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+    const page = bookFor(mintA.publicKey, mintB.publicKey, 1);
+
+    const pageAccounts = {
+      ...accounts,
+      escrow: pageEscrow,
+      vault: spl.getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        pageEscrow,
+        true,
+        tokenProgram
+      ),
+      book: page,
+    };
+
+    await program.methods
+      .initBook(1)
+      .accounts({ payer: provider.publicKey, mintA: mintA.publicKey, mintB: mintB.publicKey })
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .make(pageSeed, new anchor.BN(1e5), new anchor.BN(1e5), null, new anchor.BN(0), null, false)
+      .accounts(pageAccounts)
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    assert.equal((await program.account.escrow.fetch(pageEscrow)).bookPage, 1);
+
+    // The page starts empty and grows by one entry, paid for by the maker.
+    const pageInfo = await provider.connection.getAccountInfo(page);
+    assert.equal(pageInfo.data.length, 81 + 48);
+
+    // Only the page the offer is listed on is accepted.
+    try {
+      await program.methods
+        .take(new anchor.BN(1e5), null)
+        .accounts({ ...pageAccounts, book: accounts.book })
+        .signers([taker])
+        .rpc();
+      assert.fail("take should reject another page of the book");
+    } catch (err) {
+      assert.include(err.toString(), "WrongBookPage");
+    }
+
+    // The book is optional on refund, so it can never lock the maker's funds.
+    await program.methods
+      .refund()
+      .accounts({ ...pageAccounts, book: null })
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    let listed = await program.account.orderBook.fetch(page);
+    assert.isTrue(listed.entries.some((e) => e.escrow.equals(pageEscrow)));
+
+    // Anyone can then clear the stale entry.
+    await program.methods
+      .pruneBook()
+      .accounts({ book: page })
+      .remainingAccounts([{ pubkey: pageEscrow, isSigner: false, isWritable: false }])
+      .rpc()
+      .then(confirm);
+
+    listed = await program.account.orderBook.fetch(page);
+    assert.isFalse(listed.entries.some((e) => e.escrow.equals(pageEscrow)));
+  });
 });
\ No newline at end of file
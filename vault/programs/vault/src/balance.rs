@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::VaultState;
+
+#[derive(Accounts)]
+pub struct Balance<'info> {
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> Balance<'info> {
+    pub fn balance(&self) -> Result<VaultBalance> {
+        let lamports = self.vault.lamports();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+
+        let (withdrawable, locked) = if self.vault_state.is_locked()? {
+            (0, lamports.saturating_sub(rent_exempt_minimum))
+        } else {
+            (
+                self.vault_state.free_balance(lamports)?,
+                self.vault_state.reserved.min(lamports),
+            )
+        };
+
+        Ok(VaultBalance {
+            lamports,
+            withdrawable,
+            locked,
+            rent_exempt_minimum,
+        })
+    }
+}
+
+/// Returned by the `balance` view through the transaction return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultBalance {
+    pub lamports: u64,
+    /// What `withdraw` with `WITHDRAW_MAX` would send right now.
+    pub withdrawable: u64,
+    /// Lamports held back by the time lock or reserved for open streams.
+    pub locked: u64,
+    /// Lamports the vault keeps so it stays rent exempt until it is closed.
+    pub rent_exempt_minimum: u64,
+}
@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+
+use crate::{VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct GrantAllowance<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Only used as a seed and recorded as the delegate key.
+    pub delegate: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"delegate", vault_state.key().as_ref(), delegate.key().as_ref()],
+        space = 8 + Delegate::INIT_SPACE,
+        bump,
+    )]
+    pub delegate_record: Account<'info, Delegate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GrantAllowance<'info> {
+    pub fn grant_allowance(
+        &mut self,
+        bumps: &GrantAllowanceBumps,
+        allowance: u64,
+        period: AllowancePeriod,
+    ) -> Result<()> {
+        if let AllowancePeriod::Window(seconds) = period {
+            require!(seconds > 0, VaultError::InvalidAllowancePeriod);
+        }
+
+        let record = &mut self.delegate_record;
+
+        // A fresh record, or one whose period kind changed, starts a new period.
+        // Otherwise the amount already spent in the current period still counts.
+        if record.vault_state == Pubkey::default() || record.period != period {
+            record.period_start = period.current_start(&Clock::get()?, None);
+            record.spent = 0;
+        }
+
+        record.vault_state = self.vault_state.key();
+        record.delegate = self.delegate.key();
+        record.allowance = allowance;
+        record.period = period;
+        record.bump = bumps.delegate_record;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReduceAllowance<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        has_one = vault_state,
+        seeds = [b"delegate", vault_state.key().as_ref(), delegate_record.delegate.as_ref()],
+        bump = delegate_record.bump,
+    )]
+    pub delegate_record: Account<'info, Delegate>,
+}
+
+impl<'info> ReduceAllowance<'info> {
+    pub fn reduce_allowance(&mut self, allowance: u64) -> Result<()> {
+        require!(
+            allowance < self.delegate_record.allowance,
+            VaultError::AllowanceNotReduced
+        );
+
+        self.delegate_record.allowance = allowance;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RevokeAllowance<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        close = signer,
+        has_one = vault_state,
+        seeds = [b"delegate", vault_state.key().as_ref(), delegate_record.delegate.as_ref()],
+        bump = delegate_record.bump,
+    )]
+    pub delegate_record: Account<'info, Delegate>,
+}
+
+impl<'info> RevokeAllowance<'info> {
+    pub fn revoke_allowance(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Delegate {
+    pub vault_state: Pubkey,
+    pub delegate: Pubkey,
+    pub allowance: u64,
+    pub period: AllowancePeriod,
+    /// Epoch number or unix timestamp at which the current period began.
+    pub period_start: i64,
+    pub spent: u64,
+    pub bump: u8,
+}
+
+impl Delegate {
+    /// Lamports the delegate can still withdraw in the period containing `clock`.
+    pub fn remaining(&self, clock: &Clock) -> u64 {
+        if self.period.current_start(clock, Some(self.period_start)) != self.period_start {
+            return self.allowance;
+        }
+
+        self.allowance.saturating_sub(self.spent)
+    }
+
+    /// Rolls the period forward if it has elapsed, then charges `amount`
+    /// against what is left of the allowance.
+    pub fn spend(&mut self, amount: u64, clock: &Clock) -> Result<()> {
+        let current_start = self.period.current_start(clock, Some(self.period_start));
+
+        if current_start != self.period_start {
+            self.period_start = current_start;
+            self.spent = 0;
+        }
+
+        let spent = self
+            .spent
+            .checked_add(amount)
+            .ok_or(VaultError::AllowanceExceeded)?;
+        require!(spent <= self.allowance, VaultError::AllowanceExceeded);
+
+        self.spent = spent;
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum AllowancePeriod {
+    /// The allowance resets at every epoch boundary.
+    Epoch,
+    /// The allowance resets once this many seconds have passed since the
+    /// start of the current window.
+    Window(i64),
+}
+
+impl AllowancePeriod {
+    /// Returns the start of the period containing `clock`. Windows are laid
+    /// back to back from `previous_start`, so a late withdrawal does not shift
+    /// the schedule.
+    pub fn current_start(&self, clock: &Clock, previous_start: Option<i64>) -> i64 {
+        match (*self, previous_start) {
+            (AllowancePeriod::Epoch, _) => clock.epoch as i64,
+            (AllowancePeriod::Window(_), None) => clock.unix_timestamp,
+            (AllowancePeriod::Window(seconds), Some(start)) => {
+                let elapsed = clock.unix_timestamp.saturating_sub(start).max(0);
+                start + elapsed - elapsed % seconds
+            }
+        }
+    }
+}
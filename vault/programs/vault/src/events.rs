@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultInitialized {
+    pub vault_state: Pubkey,
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub slot: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultDeposited {
+    pub vault_state: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+    pub slot: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultWithdrawn {
+    pub vault_state: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+    pub slot: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultClosed {
+    pub vault_state: Pubkey,
+    pub owner: Pubkey,
+    /// Lamports swept from the vault to the owner on close.
+    pub amount: u64,
+    pub slot: u64,
+}
@@ -0,0 +1,224 @@
+//! Off-chain helpers that rebuild a vault's history from transaction logs.
+//!
+//! Anchor writes each event as a `Program data: <base64>` log line while the
+//! emitting program is on top of the invocation stack. The parser follows the
+//! `invoke` / `success` / `failed` lines to attribute every data line to the
+//! right program, so events logged by other programs in the same transaction
+//! are ignored.
+
+use anchor_lang::{prelude::*, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{VaultClosed, VaultDeposited, VaultInitialized, VaultWithdrawn};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VaultEvent {
+    Initialized(VaultInitialized),
+    Deposited(VaultDeposited),
+    Withdrawn(VaultWithdrawn),
+    Closed(VaultClosed),
+}
+
+impl VaultEvent {
+    pub fn vault_state(&self) -> Pubkey {
+        match self {
+            VaultEvent::Initialized(event) => event.vault_state,
+            VaultEvent::Deposited(event) => event.vault_state,
+            VaultEvent::Withdrawn(event) => event.vault_state,
+            VaultEvent::Closed(event) => event.vault_state,
+        }
+    }
+
+    /// Decodes the payload of a `Program data:` line, or returns `None` if it
+    /// is not one of this program's events.
+    pub fn decode(data: &[u8]) -> Option<VaultEvent> {
+        fn strip<T: Discriminator + AnchorDeserialize>(data: &[u8]) -> Option<T> {
+            let mut body = data.strip_prefix(T::DISCRIMINATOR)?;
+            T::deserialize(&mut body).ok()
+        }
+
+        strip::<VaultInitialized>(data)
+            .map(VaultEvent::Initialized)
+            .or_else(|| strip::<VaultDeposited>(data).map(VaultEvent::Deposited))
+            .or_else(|| strip::<VaultWithdrawn>(data).map(VaultEvent::Withdrawn))
+            .or_else(|| strip::<VaultClosed>(data).map(VaultEvent::Closed))
+    }
+}
+
+/// Returns the vault events emitted by `program_id` in one transaction's logs,
+/// in the order they were logged.
+pub fn parse_logs<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<VaultEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs.iter().map(AsRef::as_ref) {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if stack.last() == Some(&program_id.as_str()) {
+                if let Some(event) = STANDARD
+                    .decode(data)
+                    .ok()
+                    .and_then(|d| VaultEvent::decode(&d))
+                {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let (Some(id), Some(status)) = (words.next(), words.next()) else {
+                continue;
+            };
+
+            match status {
+                "invoke" => stack.push(id),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
+
+/// Running ledger of one vault, rebuilt from its events. The lamports swept
+/// by `close` count as withdrawn, so a closed vault balances to zero.
+/// Delegated stake counts as withdrawn until it is pulled back, and recovery
+/// shows up as a withdrawal from the old vault and a deposit into the new one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VaultHistory {
+    pub vault_state: Pubkey,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub last_activity_slot: u64,
+    pub closed: bool,
+    pub events: Vec<VaultEvent>,
+}
+
+impl VaultHistory {
+    pub fn new(vault_state: Pubkey) -> Self {
+        Self {
+            vault_state,
+            ..Self::default()
+        }
+    }
+
+    /// Applies the events of one transaction. Pass transactions oldest first;
+    /// events of other vaults are skipped.
+    pub fn apply<S: AsRef<str>>(&mut self, program_id: &Pubkey, logs: &[S]) {
+        for event in parse_logs(program_id, logs) {
+            if event.vault_state() != self.vault_state {
+                continue;
+            }
+
+            match &event {
+                VaultEvent::Initialized(e) => self.last_activity_slot = e.slot,
+                VaultEvent::Deposited(e) => {
+                    self.total_deposited += e.amount;
+                    self.last_activity_slot = e.slot;
+                }
+                VaultEvent::Withdrawn(e) => {
+                    self.total_withdrawn += e.amount;
+                    self.last_activity_slot = e.slot;
+                }
+                VaultEvent::Closed(e) => {
+                    self.total_withdrawn += e.amount;
+                    self.last_activity_slot = e.slot;
+                    self.closed = true;
+                }
+            }
+
+            self.events.push(event);
+        }
+    }
+
+    /// Lamports the ledger expects the vault to hold.
+    pub fn balance(&self) -> u64 {
+        self.total_deposited.saturating_sub(self.total_withdrawn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_line<T: Discriminator + AnchorSerialize>(event: &T) -> String {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("{PROGRAM_DATA}{}", STANDARD.encode(data))
+    }
+
+    #[test]
+    fn rebuilds_totals_and_ignores_other_programs() {
+        let vault_state = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+
+        let deposit = VaultDeposited {
+            vault_state,
+            depositor: owner,
+            amount: 500,
+            total_deposited: 500,
+            slot: 10,
+        };
+        let withdraw = VaultWithdrawn {
+            vault_state,
+            recipient: owner,
+            amount: 200,
+            total_withdrawn: 200,
+            slot: 11,
+        };
+
+        let logs = vec![
+            format!("Program {} invoke [1]", crate::ID),
+            data_line(&deposit),
+            format!("Program {other_program} invoke [2]"),
+            // Same bytes, but logged by another program.
+            data_line(&withdraw),
+            format!("Program {other_program} success"),
+            data_line(&withdraw),
+            format!("Program {} success", crate::ID),
+        ];
+
+        let mut history = VaultHistory::new(vault_state);
+        history.apply(&crate::ID, &logs);
+
+        assert_eq!(
+            history.events,
+            vec![
+                VaultEvent::Deposited(deposit),
+                VaultEvent::Withdrawn(withdraw)
+            ]
+        );
+        assert_eq!(history.total_deposited, 500);
+        assert_eq!(history.total_withdrawn, 200);
+        assert_eq!(history.balance(), 300);
+        assert_eq!(history.last_activity_slot, 11);
+    }
+
+    #[test]
+    fn skips_events_of_other_vaults() {
+        let vault_state = Pubkey::new_unique();
+        let closed = VaultClosed {
+            vault_state: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 1,
+            slot: 3,
+        };
+
+        let logs = vec![
+            format!("Program {} invoke [1]", crate::ID),
+            data_line(&closed),
+            format!("Program {} success", crate::ID),
+        ];
+
+        let mut history = VaultHistory::new(vault_state);
+        history.apply(&crate::ID, &logs);
+
+        assert!(history.events.is_empty());
+        assert!(!history.closed);
+    }
+}
@@ -2,15 +2,54 @@
 #![allow(deprecated)]
 use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
 
+pub mod balance;
+pub mod delegate;
+pub mod events;
+#[cfg(not(target_os = "solana"))]
+pub mod history;
+pub mod multisig;
+pub mod recovery;
+pub mod staking;
+pub mod stream;
+pub mod token_vault;
+
+pub use balance::*;
+pub use delegate::*;
+pub use events::*;
+pub use multisig::*;
+pub use recovery::*;
+pub use staking::*;
+pub use stream::*;
+pub use token_vault::*;
+
 declare_id!("Aww3qCodkpqeVb2akSBLumb8SVYkSSGbPrV14NJZ9KnJ");
 
+#[constant]
+pub const MAX_TOKEN_VAULTS: usize = 8;
+
+#[constant]
+pub const MAX_VAULTS_PER_OWNER: usize = 16;
+
+#[constant]
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+
+#[constant]
+pub const MAX_GUARDIANS: usize = 5;
+
+#[constant]
+pub const MAX_STAKE_ACCOUNTS: usize = 8;
+
+/// Passed as the `withdraw` amount to take everything currently withdrawable.
+#[constant]
+pub const WITHDRAW_MAX: u64 = u64::MAX;
+
 #[program]
 pub mod vault {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, vault_id: u64, lock: Option<LockUntil>) -> Result<()> {
         // msg!("Greetings from: {:?}", ctx.program_id);
-        ctx.accounts.initialize(&ctx.bumps)
+        ctx.accounts.initialize(&ctx.bumps, vault_id, lock)
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
@@ -22,14 +61,115 @@ pub mod vault {
         ctx.accounts.withdraw(amount)
     }
 
-    pub fn close(ctx: Context<Close>) -> Result<()> {
-        ctx.accounts.close()
+    pub fn close<'info>(ctx: Context<'_, '_, 'info, 'info, Close<'info>>) -> Result<()> {
+        ctx.accounts.close(ctx.remaining_accounts)
+    }
+
+    pub fn extend_lock(ctx: Context<ExtendLock>, lock: LockUntil) -> Result<()> {
+        ctx.accounts.extend_lock(lock)
+    }
+
+    pub fn init_token_vault(ctx: Context<InitTokenVault>) -> Result<()> {
+        ctx.accounts.init_token_vault()
+    }
+
+    pub fn deposit_token(ctx: Context<DepositToken>, amount: u64) -> Result<()> {
+        ctx.accounts.deposit_token(amount)
+    }
+
+    pub fn withdraw_token(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_token(amount)
+    }
+
+    pub fn grant_allowance(ctx: Context<GrantAllowance>, allowance: u64, period: AllowancePeriod) -> Result<()> {
+        ctx.accounts.grant_allowance(&ctx.bumps, allowance, period)
+    }
+
+    pub fn reduce_allowance(ctx: Context<ReduceAllowance>, allowance: u64) -> Result<()> {
+        ctx.accounts.reduce_allowance(allowance)
+    }
+
+    pub fn revoke_allowance(ctx: Context<RevokeAllowance>) -> Result<()> {
+        ctx.accounts.revoke_allowance()
+    }
+
+    pub fn enable_multisig(ctx: Context<EnableMultisig>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        ctx.accounts.enable_multisig(signers, threshold)
+    }
+
+    pub fn propose(ctx: Context<Propose>, action: ProposalAction, expires_at: i64) -> Result<()> {
+        ctx.accounts.propose(&ctx.bumps, action, expires_at)
+    }
+
+    pub fn approve(ctx: Context<Approve>) -> Result<()> {
+        ctx.accounts.approve()
+    }
+
+    pub fn execute_withdraw(ctx: Context<ExecuteWithdraw>) -> Result<()> {
+        ctx.accounts.execute_withdraw()
+    }
+
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        ctx.accounts.cancel_proposal()
+    }
+
+    pub fn open_stream(ctx: Context<OpenStream>, stream_id: u64, rate: u64, start: i64, end: i64) -> Result<()> {
+        ctx.accounts.open_stream(&ctx.bumps, stream_id, rate, start, end)
+    }
+
+    pub fn claim_stream(ctx: Context<ClaimStream>) -> Result<()> {
+        ctx.accounts.claim_stream()
+    }
+
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        ctx.accounts.cancel_stream()
+    }
+
+    pub fn set_guardians(ctx: Context<SetGuardians>, guardians: Vec<Pubkey>, threshold: u8, delay: i64) -> Result<()> {
+        ctx.accounts.set_guardians(guardians, threshold, delay)
+    }
+
+    pub fn start_recovery(ctx: Context<StartRecovery>, new_owner: Pubkey) -> Result<()> {
+        ctx.accounts.start_recovery(&ctx.bumps, new_owner)
+    }
+
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+        ctx.accounts.approve_recovery()
+    }
+
+    pub fn veto_recovery(ctx: Context<VetoRecovery>) -> Result<()> {
+        ctx.accounts.veto_recovery()
+    }
+
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        ctx.accounts.cancel_recovery()
+    }
+
+    pub fn execute_recovery<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteRecovery<'info>>) -> Result<()> {
+        ctx.accounts.execute_recovery(&ctx.bumps, ctx.remaining_accounts)
+    }
+
+    pub fn delegate_vault_stake(ctx: Context<DelegateVaultStake>, stake_id: u64, amount: u64) -> Result<()> {
+        ctx.accounts.delegate_vault_stake(&ctx.bumps, stake_id, amount)
+    }
+
+    pub fn deactivate_vault_stake(ctx: Context<DeactivateVaultStake>) -> Result<()> {
+        ctx.accounts.deactivate_vault_stake()
+    }
+
+    pub fn withdraw_vault_stake(ctx: Context<WithdrawVaultStake>) -> Result<()> {
+        ctx.accounts.withdraw_vault_stake()
+    }
+
+    pub fn balance(ctx: Context<Balance>) -> Result<VaultBalance> {
+        ctx.accounts.balance()
     }
 
 
 }
 
 #[derive(Accounts)]
+#[instruction(vault_id: u64)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub signer: Signer <'info>,
@@ -43,25 +183,60 @@ bump,
  #[account(
 init, 
 payer= signer,
-seeds = [b"state",signer.key().as_ref()],
+seeds = [b"state",signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
 space = 8 + VaultState::INIT_SPACE,
 bump,
 
     )]  
 
     pub vault_state: Account <'info, VaultState>,
+ #[account(
+init_if_needed,
+payer= signer,
+seeds = [b"index",signer.key().as_ref()],
+space = 8 + OwnerIndex::INIT_SPACE,
+bump,
+
+    )]
+
+    pub owner_index: Account <'info, OwnerIndex>,
         pub system_program: Program<'info, System>,
 
 }
 
 
 impl <'info> Initialize <'info>{
-    pub fn initialize (&mut self, bumps: &InitializeBumps)-> Result<()>{
+    pub fn initialize (&mut self, bumps: &InitializeBumps, vault_id: u64, lock: Option<LockUntil>)-> Result<()>{
 
         self.vault_state.state_bump = bumps.vault_state;
 
         self.vault_state.vault_bump = bumps.vault;
 
+        self.vault_state.owner = self.signer.key();
+
+        self.vault_state.vault_id = vault_id;
+
+        self.vault_state.lock = lock;
+
+        require!(
+            self.owner_index.vault_ids.len() < MAX_VAULTS_PER_OWNER,
+            VaultError::TooManyVaults
+        );
+
+        self.owner_index.owner = self.signer.key();
+        self.owner_index.bump = bumps.owner_index;
+        self.owner_index.vault_ids.push(vault_id);
+
+        let clock = Clock::get()?;
+        self.vault_state.last_activity_slot = clock.slot;
+
+        emit!(VaultInitialized {
+            vault_state: self.vault_state.key(),
+            owner: self.signer.key(),
+            vault_id,
+            slot: clock.slot,
+        });
+
         Ok(())
     }
 }
@@ -80,7 +255,8 @@ bump = vault_state.vault_bump,
     )]  
         pub vault: SystemAccount <'info>,
  #[account(
-seeds = [b"state",signer.key().as_ref()],
+    mut,
+seeds = [b"state",signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
 bump =  vault_state.state_bump,
 
     )]  
@@ -105,6 +281,17 @@ impl <'info> Deposit <'info>{
 
        transfer(cpi_ctx, amount)?;
 
+       let slot = Clock::get()?.slot;
+       self.vault_state.record_deposit(amount, slot);
+
+       emit!(VaultDeposited {
+           vault_state: self.vault_state.key(),
+           depositor: self.signer.key(),
+           amount,
+           total_deposited: self.vault_state.total_deposited,
+           slot,
+       });
+
         Ok(())
     }
 }
@@ -122,12 +309,21 @@ bump = vault_state.vault_bump,
     )]  
         pub vault: SystemAccount <'info>,
  #[account(
-seeds = [b"state",signer.key().as_ref()],
+    mut,
+seeds = [b"state",vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
 bump =  vault_state.state_bump,
 
     )]  
 
     pub vault_state: Account <'info, VaultState>,
+    // Only needed when `signer` is a delegate rather than the owner.
+ #[account(
+    mut,
+seeds = [b"delegate",vault_state.key().as_ref(), signer.key().as_ref()],
+bump = delegate_record.bump,
+    )]
+
+    pub delegate_record: Option<Account <'info, Delegate>>,
         pub system_program: Program<'info, System>,
 
 }
@@ -136,6 +332,27 @@ bump =  vault_state.state_bump,
 impl <'info> Withdraw <'info>{
     pub fn withdraw (&mut self, amount: u64)-> Result<()>{
 
+       self.vault_state.check_unlocked()?;
+
+       require!(!self.vault_state.is_multisig(), VaultError::MultisigRequired);
+
+       let mut amount = amount;
+
+       if amount == WITHDRAW_MAX {
+           amount = self.vault_state.free_balance(self.vault.lamports())?;
+
+           if let Some(delegate_record) = self.delegate_record.as_ref() {
+               amount = amount.min(delegate_record.remaining(&Clock::get()?));
+           }
+       }
+
+       self.vault_state.check_free(self.vault.lamports(), amount)?;
+
+       if self.signer.key() != self.vault_state.owner {
+           let delegate_record = self.delegate_record.as_mut().ok_or(VaultError::Unauthorized)?;
+           delegate_record.spend(amount, &Clock::get()?)?;
+       }
+
        let cpi_program = self.system_program.to_account_info();
 
        let cpi_account = Transfer{
@@ -150,6 +367,8 @@ impl <'info> Withdraw <'info>{
 
        transfer(cpi_ctx, amount)?;
 
+       record_withdrawal(&mut self.vault_state, self.signer.key(), amount)?;
+
         Ok(())
     }
 }
@@ -169,19 +388,53 @@ bump = vault_state.vault_bump,
         pub vault: SystemAccount <'info>,
  #[account(
     mut,
-seeds = [b"state",signer.key().as_ref()],
+seeds = [b"state",signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
 bump =  vault_state.state_bump,
 close = signer
     )]  
 
     pub vault_state: Account <'info, VaultState>,
+ #[account(
+    mut,
+seeds = [b"index",signer.key().as_ref()],
+bump = owner_index.bump,
+    )]
+
+    pub owner_index: Account <'info, OwnerIndex>,
+    // Only needed in multisig mode, where closing needs an approved `Close` proposal.
+ #[account(
+    mut,
+close = signer,
+has_one = vault_state,
+seeds = [b"proposal",vault_state.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+bump = proposal.bump,
+    )]
+
+    pub proposal: Option<Account <'info, Proposal>>,
         pub system_program: Program<'info, System>,
 
 }
 
 
 impl <'info> Close <'info>{
-    pub fn close (&mut self)-> Result<()>{
+    pub fn close (&mut self, token_vaults: &'info [AccountInfo<'info>])-> Result<()>{
+
+       self.vault_state.check_unlocked()?;
+
+       require!(self.vault_state.open_streams == 0, VaultError::StreamsOpen);
+
+       require!(self.vault_state.stake_accounts.is_empty(), VaultError::StakeAccountsOpen);
+
+       if self.vault_state.is_multisig() {
+           let proposal = self.proposal.as_ref().ok_or(VaultError::MultisigRequired)?;
+           require!(proposal.action == ProposalAction::Close, VaultError::WrongProposalAction);
+           proposal.check_executable(self.vault_state.threshold, &Clock::get()?)?;
+       }
+
+       sweep_token_vaults(&self.vault_state, &self.vault, &self.signer.to_account_info(), token_vaults)?;
+
+       let vault_id = self.vault_state.vault_id;
+       self.owner_index.vault_ids.retain(|id| *id != vault_id);
 
        let cpi_program = self.system_program.to_account_info();
 
@@ -195,7 +448,46 @@ impl <'info> Close <'info>{
 
        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, &seed);
 
-       transfer(cpi_ctx, self.vault.lamports())?;
+       let amount = self.vault.lamports();
+
+       transfer(cpi_ctx, amount)?;
+
+       emit!(VaultClosed {
+           vault_state: self.vault_state.key(),
+           owner: self.signer.key(),
+           amount,
+           slot: Clock::get()?.slot,
+       });
+
+        Ok(())
+    }
+}
+
+
+
+#[derive(Accounts)]
+pub struct ExtendLock<'info> {
+    pub signer: Signer <'info>,
+ #[account(
+    mut,
+seeds = [b"state",signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+bump =  vault_state.state_bump,
+    )]
+
+    pub vault_state: Account <'info, VaultState>,
+}
+
+
+impl <'info> ExtendLock <'info>{
+    pub fn extend_lock (&mut self, lock: LockUntil)-> Result<()>{
+
+       // The lock can only ever move further out, and a slot lock cannot be
+       // swapped for a timestamp lock (or vice versa) since the two are not comparable.
+       if let Some(current) = self.vault_state.lock {
+           require!(lock.extends(&current), VaultError::LockShortened);
+       }
+
+       self.vault_state.lock = Some(lock);
 
         Ok(())
     }
@@ -207,7 +499,204 @@ impl <'info> Close <'info>{
 #[derive(InitSpace)]
 pub struct VaultState{
     pub vault_bump: u8,
-    pub state_bump: u8
+    pub state_bump: u8,
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub lock: Option<LockUntil>,
+    #[max_len(MAX_TOKEN_VAULTS)]
+    pub token_vaults: Vec<Pubkey>,
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    /// Approvals needed to withdraw or close; zero while multisig mode is off.
+    pub threshold: u8,
+    pub proposal_count: u64,
+    /// Lamports committed to open streams that have not been claimed yet.
+    pub reserved: u64,
+    pub open_streams: u16,
+    #[max_len(MAX_GUARDIANS)]
+    pub guardians: Vec<Pubkey>,
+    pub recovery_threshold: u8,
+    /// Seconds the owner has to veto a recovery once guardians approved it.
+    pub recovery_delay: i64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub last_activity_slot: u64,
+    pub operation_count: u64,
+    /// Stake accounts funded by the vault and still controlled by its PDA.
+    #[max_len(MAX_STAKE_ACCOUNTS)]
+    pub stake_accounts: Vec<Pubkey>,
+}
+
+impl VaultState {
+    pub fn is_multisig(&self) -> bool {
+        self.threshold > 0
+    }
+
+    /// Lamports that can leave a vault holding `vault_lamports` while it keeps
+    /// what open streams still need plus its rent-exempt minimum.
+    pub fn free_balance(&self, vault_lamports: u64) -> Result<u64> {
+        let floor = self.reserved + Rent::get()?.minimum_balance(0);
+
+        Ok(vault_lamports.saturating_sub(floor))
+    }
+
+    pub fn check_free(&self, vault_lamports: u64, amount: u64) -> Result<()> {
+        require!(
+            amount <= self.free_balance(vault_lamports)?,
+            VaultError::InsufficientFreeBalance
+        );
+
+        Ok(())
+    }
+
+    pub fn record_deposit(&mut self, amount: u64, slot: u64) {
+        self.total_deposited += amount;
+        self.last_activity_slot = slot;
+        self.operation_count += 1;
+    }
+
+    pub fn reserve(&mut self, vault_lamports: u64, amount: u64) -> Result<()> {
+        self.check_free(vault_lamports, amount)?;
+        self.reserved += amount;
+
+        Ok(())
+    }
+
+    pub fn is_locked(&self) -> Result<bool> {
+        match self.lock {
+            Some(lock) => Ok(lock.is_active(&Clock::get()?)),
+            None => Ok(false),
+        }
+    }
+
+    pub fn check_unlocked(&self) -> Result<()> {
+        require!(!self.is_locked()?, VaultError::VaultLocked);
+
+        Ok(())
+    }
+}
+
+
+/// Lists the ids of every open vault of `owner`, so clients can derive each
+/// `[b"state", owner, vault_id]` address without scanning program accounts.
+#[account]
+#[derive(InitSpace)]
+pub struct OwnerIndex{
+    pub owner: Pubkey,
+    #[max_len(MAX_VAULTS_PER_OWNER)]
+    pub vault_ids: Vec<u64>,
+    pub bump: u8,
+}
+
+
+/// Books a lamport withdrawal on `vault_state` and emits `VaultWithdrawn`.
+/// Every path that moves lamports out of the vault goes through here, stake
+/// delegation and recovery included, except `close` which emits
+/// `VaultClosed` instead. Lamports coming back from a stake account are
+/// booked as a deposit.
+pub fn record_withdrawal(vault_state: &mut Account<VaultState>, recipient: Pubkey, amount: u64) -> Result<()> {
+    let slot = Clock::get()?.slot;
+
+    vault_state.total_withdrawn += amount;
+    vault_state.last_activity_slot = slot;
+    vault_state.operation_count += 1;
+
+    emit!(VaultWithdrawn {
+        vault_state: vault_state.key(),
+        recipient,
+        amount,
+        total_withdrawn: vault_state.total_withdrawn,
+        slot,
+    });
+
+    Ok(())
+}
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum LockUntil {
+    Slot(u64),
+    Timestamp(i64),
+}
+
+impl LockUntil {
+    pub fn is_active(&self, clock: &Clock) -> bool {
+        match *self {
+            LockUntil::Slot(slot) => clock.slot < slot,
+            LockUntil::Timestamp(ts) => clock.unix_timestamp < ts,
+        }
+    }
+
+    pub fn extends(&self, current: &LockUntil) -> bool {
+        match (*self, *current) {
+            (LockUntil::Slot(new), LockUntil::Slot(old)) => new >= old,
+            (LockUntil::Timestamp(new), LockUntil::Timestamp(old)) => new >= old,
+            _ => false,
+        }
+    }
+}
+
+
+#[error_code]
+pub enum VaultError {
+    #[msg("Vault is locked until its unlock slot or timestamp")]
+    VaultLocked,
+    #[msg("Lock can only be extended, never shortened or changed to another kind")]
+    LockShortened,
+    #[msg("Vault already tracks the maximum number of token vaults")]
+    TooManyTokenVaults,
+    #[msg("Mint has no token vault in this vault state")]
+    UnknownTokenVault,
+    #[msg("Token vault accounts do not match the tracked token vaults")]
+    TokenVaultAccountsMismatch,
+    #[msg("Owner already has the maximum number of open vaults")]
+    TooManyVaults,
+    #[msg("Signer is neither the vault owner nor one of its delegates")]
+    Unauthorized,
+    #[msg("Withdrawal exceeds the delegate's remaining allowance")]
+    AllowanceExceeded,
+    #[msg("New allowance must be lower than the current one")]
+    AllowanceNotReduced,
+    #[msg("Allowance window must be longer than zero seconds")]
+    InvalidAllowancePeriod,
+    #[msg("Multisig mode is already enabled")]
+    MultisigAlreadyEnabled,
+    #[msg("Signers must be unique, non-empty and at most the maximum, with 0 < threshold <= signers")]
+    InvalidMultisig,
+    #[msg("Vault is in multisig mode; this action needs an approved proposal")]
+    MultisigRequired,
+    #[msg("Signer is not one of the vault's multisig signers")]
+    NotMultisigSigner,
+    #[msg("Signer already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal has expired")]
+    ProposalExpired,
+    #[msg("Proposal does not have enough approvals")]
+    ThresholdNotMet,
+    #[msg("Proposal action does not match this instruction")]
+    WrongProposalAction,
+    #[msg("Stream needs a non-zero rate and an end after its start")]
+    InvalidStream,
+    #[msg("Amount exceeds the vault balance not reserved for streams or rent")]
+    InsufficientFreeBalance,
+    #[msg("Nothing has accrued on this stream since the last claim")]
+    NothingToClaim,
+    #[msg("Vault still has open streams")]
+    StreamsOpen,
+    #[msg("Guardians must be unique, exclude the owner and fit the threshold, with a positive delay")]
+    InvalidGuardians,
+    #[msg("Signer is not one of the vault's guardians")]
+    NotGuardian,
+    #[msg("Recovery has not reached the guardian threshold")]
+    RecoveryNotApproved,
+    #[msg("Recovery delay has not passed yet")]
+    RecoveryDelayActive,
+    #[msg("Vault already tracks the maximum number of stake accounts")]
+    TooManyStakeAccounts,
+    #[msg("Stake account is not tracked by this vault")]
+    UnknownStakeAccount,
+    #[msg("Vault still has stake accounts; withdraw them first")]
+    StakeAccountsOpen,
 }
 
 
@@ -0,0 +1,286 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{record_withdrawal, VaultError, VaultState, MAX_MULTISIG_SIGNERS};
+
+#[derive(Accounts)]
+pub struct EnableMultisig<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> EnableMultisig<'info> {
+    pub fn enable_multisig(&mut self, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        // Once enabled, the signer set is what protects the vault, so the
+        // owner key alone must not be able to swap it out again.
+        require!(!self.vault_state.is_multisig(), VaultError::MultisigAlreadyEnabled);
+
+        require!(
+            !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+            VaultError::InvalidMultisig
+        );
+        require!(
+            threshold > 0 && threshold as usize <= signers.len(),
+            VaultError::InvalidMultisig
+        );
+
+        for (i, key) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(key), VaultError::InvalidMultisig);
+        }
+
+        self.vault_state.signers = signers;
+        self.vault_state.threshold = threshold;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.signers.contains(&signer.key()) @ VaultError::NotMultisigSigner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"proposal", vault_state.key().as_ref(), vault_state.proposal_count.to_le_bytes().as_ref()],
+        space = 8 + Proposal::INIT_SPACE,
+        bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Propose<'info> {
+    pub fn propose(
+        &mut self,
+        bumps: &ProposeBumps,
+        action: ProposalAction,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            VaultError::ProposalExpired
+        );
+
+        self.proposal.set_inner(Proposal {
+            vault_state: self.vault_state.key(),
+            proposer: self.signer.key(),
+            id: self.vault_state.proposal_count,
+            action,
+            approvals: vec![self.signer.key()],
+            expires_at,
+            bump: bumps.proposal,
+        });
+
+        self.vault_state.proposal_count += 1;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Approve<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.signers.contains(&signer.key()) @ VaultError::NotMultisigSigner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        has_one = vault_state,
+        seeds = [b"proposal", vault_state.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+impl<'info> Approve<'info> {
+    pub fn approve(&mut self) -> Result<()> {
+        require!(
+            !self.proposal.is_expired(&Clock::get()?),
+            VaultError::ProposalExpired
+        );
+        require!(
+            !self.proposal.approvals.contains(&self.signer.key()),
+            VaultError::AlreadyApproved
+        );
+
+        self.proposal.approvals.push(self.signer.key());
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdraw<'info> {
+    pub signer: Signer<'info>,
+
+    /// CHECK: Checked against the recipient stored in the proposal.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the proposal rent; checked through `has_one`.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = vault_state,
+        has_one = proposer,
+        seeds = [b"proposal", vault_state.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExecuteWithdraw<'info> {
+    pub fn execute_withdraw(&mut self) -> Result<()> {
+        self.vault_state.check_unlocked()?;
+        self.proposal
+            .check_executable(self.vault_state.threshold, &Clock::get()?)?;
+
+        let ProposalAction::Withdraw { amount, recipient } = self.proposal.action else {
+            return err!(VaultError::WrongProposalAction);
+        };
+
+        require_keys_eq!(self.recipient.key(), recipient, VaultError::WrongProposalAction);
+        self.vault_state.check_free(self.vault.lamports(), amount)?;
+
+        let vault_state_key = self.vault_state.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            vault_state_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ]];
+
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.recipient.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        transfer(cpi_ctx, amount)?;
+
+        record_withdrawal(&mut self.vault_state, recipient, amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    pub signer: Signer<'info>,
+
+    /// CHECK: Receives the proposal rent; checked through `has_one`.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = vault_state,
+        has_one = proposer,
+        seeds = [b"proposal", vault_state.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+impl<'info> CancelProposal<'info> {
+    pub fn cancel_proposal(&mut self) -> Result<()> {
+        // The proposer and the owner may withdraw a proposal at any time;
+        // anyone else can only clean it up once it has expired.
+        let signer = self.signer.key();
+        require!(
+            signer == self.proposal.proposer
+                || signer == self.vault_state.owner
+                || self.proposal.is_expired(&Clock::get()?),
+            VaultError::Unauthorized
+        );
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub vault_state: Pubkey,
+    pub proposer: Pubkey,
+    pub id: u64,
+    pub action: ProposalAction,
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub approvals: Vec<Pubkey>,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub fn is_expired(&self, clock: &Clock) -> bool {
+        clock.unix_timestamp >= self.expires_at
+    }
+
+    pub fn check_executable(&self, threshold: u8, clock: &Clock) -> Result<()> {
+        require!(!self.is_expired(clock), VaultError::ProposalExpired);
+        require!(
+            self.approvals.len() >= threshold as usize,
+            VaultError::ThresholdNotMet
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ProposalAction {
+    Withdraw { amount: u64, recipient: Pubkey },
+    Close,
+}
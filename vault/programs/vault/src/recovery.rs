@@ -0,0 +1,387 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    record_withdrawal, sweep_token_vaults, OwnerIndex, VaultDeposited, VaultError, VaultState,
+    MAX_GUARDIANS, MAX_VAULTS_PER_OWNER,
+};
+
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> SetGuardians<'info> {
+    pub fn set_guardians(&mut self, guardians: Vec<Pubkey>, threshold: u8, delay: i64) -> Result<()> {
+        // An empty guardian set with a zero threshold turns recovery off.
+        require!(guardians.len() <= MAX_GUARDIANS, VaultError::InvalidGuardians);
+        require!(
+            threshold as usize <= guardians.len() && (threshold > 0 || guardians.is_empty()),
+            VaultError::InvalidGuardians
+        );
+        require!(delay > 0, VaultError::InvalidGuardians);
+
+        for (i, key) in guardians.iter().enumerate() {
+            require!(
+                !guardians[..i].contains(key) && *key != self.vault_state.owner,
+                VaultError::InvalidGuardians
+            );
+        }
+
+        self.vault_state.guardians = guardians;
+        self.vault_state.recovery_threshold = threshold;
+        self.vault_state.recovery_delay = delay;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct StartRecovery<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.guardians.contains(&signer.key()) @ VaultError::NotGuardian,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"recovery", vault_state.key().as_ref()],
+        space = 8 + Recovery::INIT_SPACE,
+        bump,
+    )]
+    pub recovery: Account<'info, Recovery>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> StartRecovery<'info> {
+    pub fn start_recovery(&mut self, bumps: &StartRecoveryBumps, new_owner: Pubkey) -> Result<()> {
+        require_keys_neq!(new_owner, self.vault_state.owner, VaultError::InvalidGuardians);
+
+        self.recovery.set_inner(Recovery {
+            vault_state: self.vault_state.key(),
+            new_owner,
+            initiator: self.signer.key(),
+            approvals: vec![self.signer.key()],
+            cancellations: Vec::new(),
+            unlock_at: None,
+            bump: bumps.recovery,
+        });
+
+        self.recovery.start_delay_if_approved(&self.vault_state, &Clock::get()?);
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.guardians.contains(&signer.key()) @ VaultError::NotGuardian,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        has_one = vault_state,
+        seeds = [b"recovery", vault_state.key().as_ref()],
+        bump = recovery.bump,
+    )]
+    pub recovery: Account<'info, Recovery>,
+}
+
+impl<'info> ApproveRecovery<'info> {
+    pub fn approve_recovery(&mut self) -> Result<()> {
+        require!(
+            !self.recovery.approvals.contains(&self.signer.key()),
+            VaultError::AlreadyApproved
+        );
+
+        self.recovery.approvals.push(self.signer.key());
+        self.recovery.start_delay_if_approved(&self.vault_state, &Clock::get()?);
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct VetoRecovery<'info> {
+    pub signer: Signer<'info>,
+
+    /// CHECK: Receives the recovery rent; checked through `has_one`.
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        close = initiator,
+        has_one = vault_state,
+        has_one = initiator,
+        seeds = [b"recovery", vault_state.key().as_ref()],
+        bump = recovery.bump,
+    )]
+    pub recovery: Account<'info, Recovery>,
+}
+
+impl<'info> VetoRecovery<'info> {
+    pub fn veto_recovery(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    pub signer: Signer<'info>,
+
+    /// CHECK: Receives the recovery rent; checked through `has_one`.
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.guardians.contains(&signer.key()) @ VaultError::NotGuardian,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        has_one = vault_state,
+        has_one = initiator,
+        seeds = [b"recovery", vault_state.key().as_ref()],
+        bump = recovery.bump,
+    )]
+    pub recovery: Account<'info, Recovery>,
+}
+
+impl<'info> CancelRecovery<'info> {
+    /// Votes to drop the pending recovery. Once the guardian threshold votes
+    /// to cancel, the recovery is closed so a new one can be started, which
+    /// keeps a single guardian from parking a bad `new_owner` on the vault.
+    pub fn cancel_recovery(&mut self) -> Result<()> {
+        require!(
+            !self.recovery.cancellations.contains(&self.signer.key()),
+            VaultError::AlreadyApproved
+        );
+
+        self.recovery.cancellations.push(self.signer.key());
+
+        if self.recovery.cancellations.len() >= self.vault_state.recovery_threshold as usize {
+            self.recovery.close(self.initiator.to_account_info())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+
+    /// CHECK: Receives the recovery rent; checked through `has_one`.
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = new_owner,
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [b"index", vault_state.owner.as_ref()],
+        bump = owner_index.bump,
+    )]
+    pub owner_index: Box<Account<'info, OwnerIndex>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", new_vault_state.key().as_ref()],
+        bump,
+    )]
+    pub new_vault: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = new_owner,
+        seeds = [b"state", new_owner.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        space = 8 + VaultState::INIT_SPACE,
+        bump,
+    )]
+    pub new_vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        seeds = [b"index", new_owner.key().as_ref()],
+        space = 8 + OwnerIndex::INIT_SPACE,
+        bump,
+    )]
+    pub new_owner_index: Box<Account<'info, OwnerIndex>>,
+
+    #[account(
+        mut,
+        close = initiator,
+        has_one = vault_state,
+        has_one = new_owner,
+        has_one = initiator,
+        seeds = [b"recovery", vault_state.key().as_ref()],
+        bump = recovery.bump,
+    )]
+    pub recovery: Box<Account<'info, Recovery>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExecuteRecovery<'info> {
+    /// Moves the lamports, token vaults and settings of `vault_state` to the
+    /// `[b"state", new_owner, vault_id]` PDA and its vault.
+    ///
+    /// `token_vaults` follows the layout of `sweep_token_vaults`, with the
+    /// new vault's ATAs (created beforehand) as destinations.
+    pub fn execute_recovery(
+        &mut self,
+        bumps: &ExecuteRecoveryBumps,
+        token_vaults: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let unlock_at = self.recovery.unlock_at.ok_or(VaultError::RecoveryNotApproved)?;
+        require!(
+            Clock::get()?.unix_timestamp >= unlock_at,
+            VaultError::RecoveryDelayActive
+        );
+
+        // Streams point at the old state and would be orphaned by the move.
+        require!(self.vault_state.open_streams == 0, VaultError::StreamsOpen);
+        // Stake accounts name the old vault PDA as their authority.
+        require!(
+            self.vault_state.stake_accounts.is_empty(),
+            VaultError::StakeAccountsOpen
+        );
+
+        sweep_token_vaults(
+            &self.vault_state,
+            &self.vault,
+            &self.new_vault.to_account_info(),
+            token_vaults,
+        )?;
+
+        let vault_state_key = self.vault_state.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            vault_state_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ]];
+
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.new_vault.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        let amount = self.vault.lamports();
+        transfer(cpi_ctx, amount)?;
+
+        // Book the move as a withdrawal from the old vault and a deposit into
+        // the new one, so both ledgers balance and the history sees the move.
+        record_withdrawal(&mut self.vault_state, self.new_vault.key(), amount)?;
+
+        let vault_id = self.vault_state.vault_id;
+        let mut state = (**self.vault_state).clone();
+        state.owner = self.new_owner.key();
+        state.vault_bump = bumps.new_vault;
+        state.state_bump = bumps.new_vault_state;
+        self.new_vault_state.set_inner(state);
+
+        let slot = Clock::get()?.slot;
+        self.new_vault_state.record_deposit(amount, slot);
+
+        emit!(VaultDeposited {
+            vault_state: self.new_vault_state.key(),
+            depositor: self.vault.key(),
+            amount,
+            total_deposited: self.new_vault_state.total_deposited,
+            slot,
+        });
+
+        self.owner_index.vault_ids.retain(|id| *id != vault_id);
+
+        require!(
+            self.new_owner_index.vault_ids.len() < MAX_VAULTS_PER_OWNER,
+            VaultError::TooManyVaults
+        );
+
+        self.new_owner_index.owner = self.new_owner.key();
+        self.new_owner_index.bump = bumps.new_owner_index;
+        self.new_owner_index.vault_ids.push(vault_id);
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Recovery {
+    pub vault_state: Pubkey,
+    pub new_owner: Pubkey,
+    pub initiator: Pubkey,
+    #[max_len(MAX_GUARDIANS)]
+    pub approvals: Vec<Pubkey>,
+    /// Guardians that voted to cancel; the threshold of them closes the recovery.
+    #[max_len(MAX_GUARDIANS)]
+    pub cancellations: Vec<Pubkey>,
+    /// Set once the guardian threshold is reached. The owner can veto at any
+    /// point before the recovery is executed.
+    pub unlock_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl Recovery {
+    pub fn start_delay_if_approved(&mut self, vault_state: &VaultState, clock: &Clock) {
+        if self.unlock_at.is_none()
+            && self.approvals.len() >= vault_state.recovery_threshold as usize
+        {
+            self.unlock_at = Some(clock.unix_timestamp + vault_state.recovery_delay);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke, invoke_signed},
+        stake::{
+            self,
+            state::{Authorized, Lockup, StakeStateV2},
+        },
+    },
+    system_program::{create_account, CreateAccount},
+};
+use anchor_spl::stake::{
+    deactivate_stake, withdraw as withdraw_stake, DeactivateStake, Stake, Withdraw as WithdrawStake,
+};
+
+use crate::{record_withdrawal, VaultDeposited, VaultError, VaultState, MAX_STAKE_ACCOUNTS};
+
+#[derive(Accounts)]
+#[instruction(stake_id: u64)]
+pub struct DelegateVaultStake<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// CHECK: Created here as a stake account, at the address below.
+    #[account(
+        mut,
+        seeds = [b"stake", vault_state.key().as_ref(), stake_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the stake program on delegation.
+    pub vote_account: UncheckedAccount<'info>,
+
+    /// CHECK: Stake config account, unused by the stake program but still expected.
+    #[account(address = stake::config::ID)]
+    pub stake_config: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: Stake history sysvar.
+    #[account(address = anchor_lang::solana_program::sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    pub stake_program: Program<'info, Stake>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DelegateVaultStake<'info> {
+    /// Moves `amount` lamports from the vault into a new stake account whose
+    /// stake and withdraw authority is the vault PDA, then delegates it.
+    pub fn delegate_vault_stake(
+        &mut self,
+        bumps: &DelegateVaultStakeBumps,
+        stake_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        // Staked lamports leave the vault, so this is held to the same rules
+        // as a withdrawal.
+        self.vault_state.check_unlocked()?;
+
+        require!(!self.vault_state.is_multisig(), VaultError::MultisigRequired);
+
+        require!(
+            self.vault_state.stake_accounts.len() < MAX_STAKE_ACCOUNTS,
+            VaultError::TooManyStakeAccounts
+        );
+        self.vault_state.check_free(self.vault.lamports(), amount)?;
+
+        let vault_state_key = self.vault_state.key();
+        let vault_seeds: &[&[u8]] = &[
+            b"vault",
+            vault_state_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let stake_id_bytes = stake_id.to_le_bytes();
+        let stake_seeds: &[&[u8]] = &[
+            b"stake",
+            vault_state_key.as_ref(),
+            stake_id_bytes.as_ref(),
+            &[bumps.stake_account],
+        ];
+
+        create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.vault.to_account_info(),
+                    to: self.stake_account.to_account_info(),
+                },
+                &[vault_seeds, stake_seeds],
+            ),
+            amount,
+            StakeStateV2::size_of() as u64,
+            &stake::program::ID,
+        )?;
+
+        let authorized = Authorized {
+            staker: self.vault.key(),
+            withdrawer: self.vault.key(),
+        };
+
+        invoke(
+            &stake::instruction::initialize(
+                &self.stake_account.key(),
+                &authorized,
+                &Lockup::default(),
+            ),
+            &[
+                self.stake_account.to_account_info(),
+                self.rent.to_account_info(),
+            ],
+        )?;
+
+        invoke_signed(
+            &stake::instruction::delegate_stake(
+                &self.stake_account.key(),
+                &self.vault.key(),
+                &self.vote_account.key(),
+            ),
+            &[
+                self.stake_account.to_account_info(),
+                self.vote_account.to_account_info(),
+                self.clock.to_account_info(),
+                self.stake_history.to_account_info(),
+                self.stake_config.to_account_info(),
+                self.vault.to_account_info(),
+            ],
+            &[vault_seeds],
+        )?;
+
+        self.vault_state.stake_accounts.push(self.stake_account.key());
+
+        record_withdrawal(&mut self.vault_state, self.stake_account.key(), amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct DeactivateVaultStake<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.stake_accounts.contains(&stake_account.key()) @ VaultError::UnknownStakeAccount,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: One of the stake accounts tracked by `vault_state`.
+    #[account(mut)]
+    pub stake_account: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_program: Program<'info, Stake>,
+}
+
+impl<'info> DeactivateVaultStake<'info> {
+    pub fn deactivate_vault_stake(&mut self) -> Result<()> {
+        let vault_state_key = self.vault_state.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            vault_state_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ]];
+
+        deactivate_stake(CpiContext::new_with_signer(
+            self.stake_program.to_account_info(),
+            DeactivateStake {
+                stake: self.stake_account.to_account_info(),
+                staker: self.vault.to_account_info(),
+                clock: self.clock.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVaultStake<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.stake_accounts.contains(&stake_account.key()) @ VaultError::UnknownStakeAccount,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: One of the stake accounts tracked by `vault_state`.
+    #[account(mut)]
+    pub stake_account: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: Stake history sysvar.
+    #[account(address = anchor_lang::solana_program::sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    pub stake_program: Program<'info, Stake>,
+}
+
+impl<'info> WithdrawVaultStake<'info> {
+    /// Pulls every lamport of a fully deactivated stake account back into the
+    /// vault, which also closes the stake account.
+    pub fn withdraw_vault_stake(&mut self) -> Result<()> {
+        let amount = self.stake_account.lamports();
+        let vault_state_key = self.vault_state.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            vault_state_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ]];
+
+        withdraw_stake(
+            CpiContext::new_with_signer(
+                self.stake_program.to_account_info(),
+                WithdrawStake {
+                    stake: self.stake_account.to_account_info(),
+                    withdrawer: self.vault.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    clock: self.clock.to_account_info(),
+                    stake_history: self.stake_history.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            None,
+        )?;
+
+        let slot = Clock::get()?.slot;
+        self.vault_state.record_deposit(amount, slot);
+
+        emit!(VaultDeposited {
+            vault_state: self.vault_state.key(),
+            depositor: self.stake_account.key(),
+            amount,
+            total_deposited: self.vault_state.total_deposited,
+            slot,
+        });
+
+        let stake_account = self.stake_account.key();
+        self.vault_state
+            .stake_accounts
+            .retain(|key| *key != stake_account);
+
+        Ok(())
+    }
+}
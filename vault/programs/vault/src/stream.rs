@@ -0,0 +1,253 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{record_withdrawal, VaultError, VaultState};
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64)]
+pub struct OpenStream<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Only recorded as the stream recipient.
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"stream", vault_state.key().as_ref(), stream_id.to_le_bytes().as_ref()],
+        space = 8 + Stream::INIT_SPACE,
+        bump,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenStream<'info> {
+    pub fn open_stream(
+        &mut self,
+        bumps: &OpenStreamBumps,
+        stream_id: u64,
+        rate: u64,
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        // Opening a stream commits vault funds to a recipient, so it is held
+        // to the same rules as a withdrawal by the owner.
+        self.vault_state.check_unlocked()?;
+        require!(!self.vault_state.is_multisig(), VaultError::MultisigRequired);
+
+        require!(rate > 0 && end > start, VaultError::InvalidStream);
+
+        let total = rate
+            .checked_mul((end - start) as u64)
+            .ok_or(VaultError::InvalidStream)?;
+
+        self.vault_state.reserve(self.vault.lamports(), total)?;
+        self.vault_state.open_streams += 1;
+
+        self.stream.set_inner(Stream {
+            vault_state: self.vault_state.key(),
+            recipient: self.recipient.key(),
+            stream_id,
+            rate,
+            start,
+            end,
+            claimed: 0,
+            bump: bumps.stream,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimStream<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.owner.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        has_one = vault_state,
+        has_one = recipient,
+        seeds = [b"stream", vault_state.key().as_ref(), stream.stream_id.to_le_bytes().as_ref()],
+        bump = stream.bump,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimStream<'info> {
+    pub fn claim_stream(&mut self) -> Result<()> {
+        let claimable = self.stream.claimable(&Clock::get()?);
+        require!(claimable > 0, VaultError::NothingToClaim);
+
+        self.stream.claimed += claimable;
+        self.vault_state.reserved -= claimable;
+
+        pay_from_vault(
+            &self.vault_state,
+            &self.vault,
+            &self.recipient.to_account_info(),
+            &self.system_program,
+            claimable,
+        )?;
+
+        record_withdrawal(&mut self.vault_state, self.recipient.key(), claimable)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Checked through `has_one` on the stream.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        close = signer,
+        has_one = vault_state,
+        has_one = recipient,
+        seeds = [b"stream", vault_state.key().as_ref(), stream.stream_id.to_le_bytes().as_ref()],
+        bump = stream.bump,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelStream<'info> {
+    pub fn cancel_stream(&mut self) -> Result<()> {
+        let claimable = self.stream.claimable(&Clock::get()?);
+        let unclaimed = self.stream.total() - self.stream.claimed;
+
+        // Whatever has not accrued yet goes back to the free vault balance,
+        // the accrued part is paid out to the recipient right away.
+        self.vault_state.reserved -= unclaimed;
+        self.vault_state.open_streams -= 1;
+
+        if claimable > 0 {
+            pay_from_vault(
+                &self.vault_state,
+                &self.vault,
+                &self.recipient.to_account_info(),
+                &self.system_program,
+                claimable,
+            )?;
+
+            record_withdrawal(&mut self.vault_state, self.recipient.key(), claimable)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn pay_from_vault<'info>(
+    vault_state: &Account<'info, VaultState>,
+    vault: &SystemAccount<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    let vault_state_key = vault_state.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        vault_state_key.as_ref(),
+        &[vault_state.vault_bump],
+    ]];
+
+    let cpi_accounts = Transfer {
+        from: vault.to_account_info(),
+        to: to.clone(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+
+    transfer(cpi_ctx, amount)
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Stream {
+    pub vault_state: Pubkey,
+    pub recipient: Pubkey,
+    pub stream_id: u64,
+    /// Lamports accrued per second between `start` and `end`.
+    pub rate: u64,
+    pub start: i64,
+    pub end: i64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl Stream {
+    pub fn total(&self) -> u64 {
+        self.rate * (self.end - self.start) as u64
+    }
+
+    pub fn accrued(&self, clock: &Clock) -> u64 {
+        let elapsed = clock.unix_timestamp.min(self.end) - self.start;
+
+        if elapsed <= 0 {
+            return 0;
+        }
+
+        self.rate * elapsed as u64
+    }
+
+    pub fn claimable(&self, clock: &Clock) -> u64 {
+        self.accrued(clock) - self.claimed
+    }
+}
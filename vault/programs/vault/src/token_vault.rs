@@ -0,0 +1,275 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{VaultError, VaultState, MAX_TOKEN_VAULTS};
+
+#[derive(Accounts)]
+pub struct InitTokenVault<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = signer,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitTokenVault<'info> {
+    pub fn init_token_vault(&mut self) -> Result<()> {
+        // `init` on the ATA already rejects a second vault for the same mint,
+        // so the only thing left to guard is the fixed capacity of the list.
+        require!(
+            self.vault_state.token_vaults.len() < MAX_TOKEN_VAULTS,
+            VaultError::TooManyTokenVaults
+        );
+
+        self.vault_state.token_vaults.push(self.mint.key());
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct DepositToken<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.token_vaults.contains(&mint.key()) @ VaultError::UnknownTokenVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = signer,
+        associated_token::token_program = token_program
+    )]
+    pub signer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> DepositToken<'info> {
+    pub fn deposit_token(&mut self, amount: u64) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from: self.signer_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault_ata.to_account_info(),
+            authority: self.signer.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawToken<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"state", signer.key().as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = signer,
+        associated_token::token_program = token_program
+    )]
+    pub signer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> WithdrawToken<'info> {
+    pub fn withdraw_token(&mut self, amount: u64) -> Result<()> {
+        self.vault_state.check_unlocked()?;
+        require!(!self.vault_state.is_multisig(), VaultError::MultisigRequired);
+
+        let vault_state_key = self.vault_state.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            vault_state_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ]];
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.signer_ata.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
+
+/// Empties and closes every token vault tracked by `vault_state`, sending the
+/// tokens to `destination`'s ATAs and the ATA rent to `destination`.
+///
+/// `accounts` must hold one `[mint, vault_ata, destination_ata, token_program]`
+/// group per tracked mint, in the same order as `vault_state.token_vaults`.
+pub fn sweep_token_vaults<'info>(
+    vault_state: &Account<'info, VaultState>,
+    vault: &SystemAccount<'info>,
+    destination: &AccountInfo<'info>,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    require_eq!(
+        accounts.len(),
+        vault_state.token_vaults.len() * 4,
+        VaultError::TokenVaultAccountsMismatch
+    );
+
+    let vault_state_key = vault_state.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        vault_state_key.as_ref(),
+        &[vault_state.vault_bump],
+    ]];
+
+    for (tracked_mint, group) in vault_state.token_vaults.iter().zip(accounts.chunks(4)) {
+        let [mint_info, vault_ata_info, destination_ata_info, token_program_info] = group else {
+            return err!(VaultError::TokenVaultAccountsMismatch);
+        };
+
+        require_keys_eq!(mint_info.key(), *tracked_mint, VaultError::TokenVaultAccountsMismatch);
+        require_keys_eq!(
+            *mint_info.owner,
+            token_program_info.key(),
+            VaultError::TokenVaultAccountsMismatch
+        );
+        require_keys_eq!(
+            vault_ata_info.key(),
+            get_associated_token_address_with_program_id(
+                &vault.key(),
+                tracked_mint,
+                &token_program_info.key()
+            ),
+            VaultError::TokenVaultAccountsMismatch
+        );
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let vault_ata = InterfaceAccount::<TokenAccount>::try_from(vault_ata_info)?;
+
+        if vault_ata.amount > 0 {
+            let destination_ata = InterfaceAccount::<TokenAccount>::try_from(destination_ata_info)?;
+            require_keys_eq!(destination_ata.owner, destination.key(), VaultError::TokenVaultAccountsMismatch);
+
+            let cpi_accounts = TransferChecked {
+                from: vault_ata_info.clone(),
+                mint: mint_info.clone(),
+                to: destination_ata_info.clone(),
+                authority: vault.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                token_program_info.clone(),
+                cpi_accounts,
+                signer_seeds,
+            );
+
+            transfer_checked(cpi_ctx, vault_ata.amount, mint.decimals)?;
+        }
+
+        let close_accounts = CloseAccount {
+            account: vault_ata_info.clone(),
+            destination: destination.clone(),
+            authority: vault.to_account_info(),
+        };
+
+        let close_ctx = CpiContext::new_with_signer(
+            token_program_info.clone(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(close_ctx)?;
+    }
+
+    Ok(())
+}
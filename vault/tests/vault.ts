@@ -1,5 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import * as spl from "@solana/spl-token";
+import { assert } from "chai";
 import { Vault } from "../target/types/vault";
 
 describe("vault", () => {
@@ -8,9 +10,600 @@ describe("vault", () => {
 
   const program = anchor.workspace.vault as Program<Vault>;
 
+  const vaultStateFor = (owner: anchor.web3.PublicKey, vaultId: number) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("state"),
+        owner.toBuffer(),
+        new anchor.BN(vaultId).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
   it("Is initialized!", async () => {
     // Add your test here.
-    const tx = await program.methods.initialize().rpc();
+    const tx = await program.methods.initialize(new anchor.BN(0), null).rpc();
     console.log("Your transaction signature", tx);
   });
+
+  it("Rejects withdrawals while locked and only extends the lock", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(owner.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    const unlockAt = Math.floor(Date.now() / 1000) + 3600;
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+
+    await program.methods
+      .initialize(new anchor.BN(0), { timestamp: [new anchor.BN(unlockAt)] })
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(1000))
+        .accounts({ signer: owner.publicKey, vaultState, delegateRecord: null })
+        .signers([owner])
+        .rpc();
+      assert.fail("withdraw should be locked");
+    } catch (err) {
+      assert.include(err.toString(), "VaultLocked");
+    }
+
+    // Staking moves lamports out of the vault too, so the lock applies.
+    try {
+      const { current } = await connection.getVoteAccounts();
+      await program.methods
+        .delegateVaultStake(new anchor.BN(0), new anchor.BN(1000))
+        .accounts({
+          signer: owner.publicKey,
+          vaultState,
+          voteAccount: new anchor.web3.PublicKey(current[0].votePubkey),
+        })
+        .signers([owner])
+        .rpc();
+      assert.fail("delegate_vault_stake should be locked");
+    } catch (err) {
+      assert.include(err.toString(), "VaultLocked");
+    }
+
+    try {
+      await program.methods
+        .extendLock({ timestamp: [new anchor.BN(unlockAt - 60)] })
+        .accounts({ signer: owner.publicKey, vaultState })
+        .signers([owner])
+        .rpc();
+      assert.fail("lock should not be shortened");
+    } catch (err) {
+      assert.include(err.toString(), "LockShortened");
+    }
+
+    await program.methods
+      .extendLock({ timestamp: [new anchor.BN(unlockAt + 60)] })
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+  });
+
+  it("Holds SPL tokens and sweeps them on close", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    const tokenProgram = spl.TOKEN_2022_PROGRAM_ID;
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(owner.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    const mint = await spl.createMint(
+      connection,
+      owner,
+      owner.publicKey,
+      null,
+      6,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    const ownerAta = await spl.createAssociatedTokenAccount(
+      connection,
+      owner,
+      mint,
+      owner.publicKey,
+      undefined,
+      tokenProgram
+    );
+    await spl.mintTo(connection, owner, mint, ownerAta, owner, 1e9, [], undefined, tokenProgram);
+
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+    const [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), vaultState.toBuffer()],
+      program.programId
+    );
+    const vaultAta = spl.getAssociatedTokenAddressSync(mint, vault, true, tokenProgram);
+
+    await program.methods
+      .initialize(new anchor.BN(0), null)
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .initTokenVault()
+      .accounts({ signer: owner.publicKey, vaultState, mint, tokenProgram })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .depositToken(new anchor.BN(1e6))
+      .accounts({ signer: owner.publicKey, vaultState, mint, tokenProgram })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .withdrawToken(new anchor.BN(4e5))
+      .accounts({ signer: owner.publicKey, vaultState, mint, tokenProgram })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .close()
+      .accounts({ signer: owner.publicKey, vaultState, proposal: null })
+      .remainingAccounts([
+        { pubkey: mint, isSigner: false, isWritable: false },
+        { pubkey: vaultAta, isSigner: false, isWritable: true },
+        { pubkey: ownerAta, isSigner: false, isWritable: true },
+        { pubkey: tokenProgram, isSigner: false, isWritable: false },
+      ])
+      .signers([owner])
+      .rpc();
+
+    const balance = await spl.getAccount(connection, ownerAta, undefined, tokenProgram);
+    assert.equal(balance.amount.toString(), (1e9).toString());
+    assert.isNull(await connection.getAccountInfo(vaultAta));
+  });
+
+  it("Keeps several vaults per owner in the owner index", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(owner.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    for (const vaultId of [1, 2, 3]) {
+      await program.methods
+        .initialize(new anchor.BN(vaultId), null)
+        .accounts({ signer: owner.publicKey })
+        .signers([owner])
+        .rpc();
+    }
+
+    await program.methods
+      .close()
+      .accounts({
+        signer: owner.publicKey,
+        vaultState: vaultStateFor(owner.publicKey, 2),
+        proposal: null,
+      })
+      .signers([owner])
+      .rpc();
+
+    const [ownerIndex] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("index"), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    const index = await program.account.ownerIndex.fetch(ownerIndex);
+    assert.deepEqual(
+      index.vaultIds.map((id) => id.toNumber()),
+      [1, 3]
+    );
+  });
+
+  it("Lets a delegate withdraw up to its allowance", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const bot = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    for (const wallet of [owner, bot]) {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(wallet.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+    }
+
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+    const [delegateRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), vaultState.toBuffer(), bot.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initialize(new anchor.BN(0), null)
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .grantAllowance(new anchor.BN(1e6), { window: [new anchor.BN(3600)] })
+      .accounts({ signer: owner.publicKey, delegate: bot.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .withdraw(new anchor.BN(6e5))
+      .accounts({ signer: bot.publicKey, vaultState, delegateRecord })
+      .signers([bot])
+      .rpc();
+
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(6e5))
+        .accounts({ signer: bot.publicKey, vaultState, delegateRecord })
+        .signers([bot])
+        .rpc();
+      assert.fail("withdrawal should exceed the allowance");
+    } catch (err) {
+      assert.include(err.toString(), "AllowanceExceeded");
+    }
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.totalDeposited.toNumber(), anchor.web3.LAMPORTS_PER_SOL);
+    assert.equal(state.totalWithdrawn.toNumber(), 6e5);
+    assert.equal(state.operationCount.toNumber(), 2);
+
+    await program.methods
+      .reduceAllowance(new anchor.BN(5e5))
+      .accounts({ signer: owner.publicKey, vaultState, delegateRecord })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .revokeAllowance()
+      .accounts({ signer: owner.publicKey, vaultState, delegateRecord })
+      .signers([owner])
+      .rpc();
+
+    assert.isNull(await connection.getAccountInfo(delegateRecord));
+  });
+
+  it("Withdraws through an approved multisig proposal", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const cosigner = anchor.web3.Keypair.generate();
+    const recipient = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    for (const wallet of [owner, cosigner]) {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(wallet.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+    }
+
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+    const [proposal] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), vaultState.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    await program.methods
+      .initialize(new anchor.BN(0), null)
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .enableMultisig([owner.publicKey, cosigner.publicKey], 2)
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(1e6))
+        .accounts({ signer: owner.publicKey, vaultState, delegateRecord: null })
+        .signers([owner])
+        .rpc();
+      assert.fail("plain withdraw should need a proposal");
+    } catch (err) {
+      assert.include(err.toString(), "MultisigRequired");
+    }
+
+    const amount = new anchor.BN(1e8);
+    await program.methods
+      .propose(
+        { withdraw: { amount, recipient: recipient.publicKey } },
+        new anchor.BN(Math.floor(Date.now() / 1000) + 600)
+      )
+      .accounts({ signer: owner.publicKey, vaultState, proposal })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .approve()
+      .accounts({ signer: cosigner.publicKey, vaultState, proposal })
+      .signers([cosigner])
+      .rpc();
+
+    await program.methods
+      .executeWithdraw()
+      .accounts({
+        signer: cosigner.publicKey,
+        recipient: recipient.publicKey,
+        proposer: owner.publicKey,
+        vaultState,
+        proposal,
+      })
+      .signers([cosigner])
+      .rpc();
+
+    assert.equal(await connection.getBalance(recipient.publicKey), amount.toNumber());
+  });
+
+  it("Streams lamports to a recipient and settles on cancel", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const contributor = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    for (const wallet of [owner, contributor]) {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(wallet.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+    }
+
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+    const streamId = new anchor.BN(7);
+    const [stream] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stream"), vaultState.toBuffer(), streamId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    await program.methods
+      .initialize(new anchor.BN(0), null)
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .openStream(streamId, new anchor.BN(1000), new anchor.BN(now - 10), new anchor.BN(now + 3600))
+      .accounts({ signer: owner.publicKey, recipient: contributor.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .claimStream()
+      .accounts({ recipient: contributor.publicKey, vaultState, stream })
+      .signers([contributor])
+      .rpc();
+
+    await program.methods
+      .cancelStream()
+      .accounts({ signer: owner.publicKey, recipient: contributor.publicKey, vaultState, stream })
+      .signers([owner])
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.reserved.toNumber(), 0);
+    assert.equal(state.openStreams, 0);
+    assert.isNull(await connection.getAccountInfo(stream));
+  });
+
+  it("Migrates a vault to a new owner after guardian recovery", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const guardian = anchor.web3.Keypair.generate();
+    const newOwner = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    for (const wallet of [owner, guardian, newOwner]) {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(wallet.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+    }
+
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+    const newVaultState = vaultStateFor(newOwner.publicKey, 0);
+    const [recovery] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("recovery"), vaultState.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initialize(new anchor.BN(0), null)
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .setGuardians([guardian.publicKey], 1, new anchor.BN(1))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    // The owner can veto a recovery they did not ask for.
+    await program.methods
+      .startRecovery(newOwner.publicKey)
+      .accounts({ signer: guardian.publicKey, vaultState })
+      .signers([guardian])
+      .rpc();
+
+    await program.methods
+      .vetoRecovery()
+      .accounts({ signer: owner.publicKey, initiator: guardian.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    // Guardians can also drop a recovery once enough of them vote to cancel.
+    await program.methods
+      .startRecovery(newOwner.publicKey)
+      .accounts({ signer: guardian.publicKey, vaultState })
+      .signers([guardian])
+      .rpc();
+
+    await program.methods
+      .cancelRecovery()
+      .accounts({ signer: guardian.publicKey, initiator: guardian.publicKey, vaultState })
+      .signers([guardian])
+      .rpc();
+
+    assert.isNull(await connection.getAccountInfo(recovery));
+
+    await program.methods
+      .startRecovery(newOwner.publicKey)
+      .accounts({ signer: guardian.publicKey, vaultState })
+      .signers([guardian])
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    await program.methods
+      .executeRecovery()
+      .accounts({
+        newOwner: newOwner.publicKey,
+        initiator: guardian.publicKey,
+        vaultState,
+        newVaultState,
+        recovery,
+      })
+      .signers([newOwner])
+      .rpc();
+
+    const migrated = await program.account.vaultState.fetch(newVaultState);
+    assert.ok(migrated.owner.equals(newOwner.publicKey));
+    assert.equal(
+      migrated.totalDeposited.sub(migrated.totalWithdrawn).toNumber(),
+      await connection.getBalance(
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("vault"), newVaultState.toBuffer()],
+          program.programId
+        )[0]
+      )
+    );
+    assert.isNull(await connection.getAccountInfo(vaultState));
+  });
+
+  it("Delegates idle vault lamports to a validator", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(owner.publicKey, 5 * anchor.web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+    const stakeId = new anchor.BN(0);
+    const [stakeAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stake"), vaultState.toBuffer(), stakeId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const { current } = await connection.getVoteAccounts();
+    const voteAccount = new anchor.web3.PublicKey(current[0].votePubkey);
+
+    await program.methods
+      .initialize(new anchor.BN(0), null)
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(3 * anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .delegateVaultStake(stakeId, new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState, voteAccount })
+      .signers([owner])
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.ok(state.stakeAccounts[0].equals(stakeAccount));
+    assert.equal(state.totalWithdrawn.toNumber(), 2 * anchor.web3.LAMPORTS_PER_SOL);
+
+    await program.methods
+      .deactivateVaultStake()
+      .accounts({ signer: owner.publicKey, vaultState, stakeAccount })
+      .signers([owner])
+      .rpc();
+
+    // Withdrawing needs the deactivation to finish at the next epoch boundary,
+    // which the local validator does not reach within this test.
+  });
+
+  it("Reports the withdrawable balance and withdraws the maximum", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(owner.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    const vaultState = vaultStateFor(owner.publicKey, 0);
+    const [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), vaultState.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initialize(new anchor.BN(0), null)
+      .accounts({ signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ signer: owner.publicKey, vaultState })
+      .signers([owner])
+      .rpc();
+
+    const balance = await program.methods.balance().accounts({ vaultState }).view();
+    const rentExemptMinimum = await connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(balance.rentExemptMinimum.toNumber(), rentExemptMinimum);
+    assert.equal(balance.locked.toNumber(), 0);
+    assert.equal(
+      balance.withdrawable.toNumber(),
+      anchor.web3.LAMPORTS_PER_SOL - rentExemptMinimum
+    );
+
+    // u64::MAX is the "withdraw everything available" sentinel.
+    await program.methods
+      .withdraw(new anchor.BN("18446744073709551615"))
+      .accounts({ signer: owner.publicKey, vaultState, delegateRecord: null })
+      .signers([owner])
+      .rpc();
+
+    assert.equal(await connection.getBalance(vault), rentExemptMinimum);
+  });
 });